@@ -1,8 +1,8 @@
 use crate::card::cost::Cost;
-use crate::player::Player;
+use crate::resources::Resources;
 
 pub const ARISTOCRAT_POINTS: u8 = 3;
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Aristocrat {
     cost: Cost,
 }
@@ -12,7 +12,10 @@ impl Aristocrat {
             cost: resources
         }
     }
-    pub fn can_be_taken_by(&self, player: &Player) -> bool {
-        player.get_production().can_pay(&self.cost)
+    pub fn cost(&self) -> &Cost {
+        &self.cost
+    }
+    pub fn can_be_taken_with(&self, production: &Resources) -> bool {
+        production.can_pay(&self.cost)
     }
 }
\ No newline at end of file
@@ -1,37 +1,25 @@
-use once_cell::sync::Lazy;
 use crate::aristocrat::Aristocrat;
-use crate::card::cost::Cost;
 
+/// Aristocrats of the deck in use, an aristocrat's id being its index
+#[derive(PartialEq)]
 pub struct AristocratStorage {
     aristocrats: Vec<Aristocrat>,
 }
 
 impl AristocratStorage {
-    fn new() -> Self {
-        let aristocrats = vec![
-            Aristocrat::new(Cost::new(0, 4, 0, 0, 4)),
-            Aristocrat::new(Cost::new(0, 3, 0, 3, 3)),
-            Aristocrat::new(Cost::new(0, 0, 4, 4, 0)),
-            Aristocrat::new(Cost::new(0, 0, 0, 4, 4)),
-            Aristocrat::new(Cost::new(0, 0, 4, 0, 4)),
-            Aristocrat::new(Cost::new(0, 3, 3, 3, 0)),
-            Aristocrat::new(Cost::new(0, 3, 3, 3, 3)),
-            Aristocrat::new(Cost::new(0, 0, 0, 4, 4)),
-            Aristocrat::new(Cost::new(0, 0, 3, 3, 3)),
-            Aristocrat::new(Cost::new(0, 3, 3, 0, 3)),
-        ];
-        
+    pub(crate) fn new(aristocrats: Vec<Aristocrat>) -> Self {
         Self { aristocrats }
     }
     
     pub fn get_aristocrat(&self, index: usize) -> &Aristocrat {
         &self.aristocrats[index]
     }
-    
 
     pub fn len(&self) -> usize {
         self.aristocrats.len()
     }
-}
 
-pub static ARISTOCRAT_STORAGE: Lazy<AristocratStorage> = Lazy::new(AristocratStorage::new);
\ No newline at end of file
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Aristocrat> {
+        self.aristocrats.iter()
+    }
+}
\ No newline at end of file
@@ -1,34 +1,27 @@
-use crate::aristocrat::Aristocrat;
-use crate::aristocrat_storage::ARISTOCRAT_STORAGE;
+use serde::{Deserialize, Serialize};
 use crate::board::rows::rows::Rows;
 use crate::resources::Resources;
+use crate::rule_set::RuleSet;
 use rand::prelude::SliceRandom;
 use rand::Rng;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Board {
-    resources: Resources,
-    rows: Rows,
-    aristocrats: Vec<usize>,  // Indices into ARISTOCRAT_STORAGE
+    pub(crate) resources: Resources,
+    pub(crate) rows: Rows,
+    pub(crate) aristocrats: Vec<usize>,  // Ids into the deck of the rules
 }
-const INITIAL_GOLD: u8 = 5;
 
 impl Board {
-    pub fn new<R: Rng>(n_aristocrats: usize, rng: &mut R) -> Self {
-        let n_resources: u8;
-        match n_aristocrats {
-            2 => {n_resources=4;}
-            3 => {n_resources=5;}
-            4 => {n_resources=7;}
-            _ => {panic!("N aristocrats must be 2, 3 or 4.");}
-        }
-        let mut aristocrat_indices: Vec<usize> = (0..ARISTOCRAT_STORAGE.len()).collect();
+    pub fn new<R: Rng>(rules: &RuleSet, rng: &mut R) -> Self {
+        let n_resources = rules.n_tokens_per_colour();
+        let mut aristocrat_indices: Vec<usize> = (0..rules.deck().n_aristocrats()).collect();
         aristocrat_indices.shuffle(rng);
 
         Self {
-            resources: Resources::new(n_resources, n_resources, n_resources, n_resources, n_resources, INITIAL_GOLD),
-            rows: Rows::new(rng),
-            aristocrats: aristocrat_indices.drain(0..n_aristocrats).collect(),
+            resources: Resources::new(n_resources, n_resources, n_resources, n_resources, n_resources, rules.n_gold()),
+            rows: Rows::new(rules.deck(), rng),
+            aristocrats: aristocrat_indices.drain(0..rules.n_aristocrats() as usize).collect(),
         }
     }
     
@@ -36,40 +29,12 @@ impl Board {
         &self.rows
     }
     
-    pub fn get_aristocrats(&self) -> Vec<&'static Aristocrat> {
-        self.aristocrats.iter().map(|&i| ARISTOCRAT_STORAGE.get_aristocrat(i)).collect()
+    /// Storage ids of the aristocrats still on the board
+    pub fn get_aristocrats(&self) -> &Vec<usize> {
+        &self.aristocrats
     }
     
     pub fn get_resources(&self) -> &Resources {
         &self.resources
     }
-    
-
-    pub fn to_builder(&self) -> BoardBuilder {
-        BoardBuilder::new(self)
-    }
 }
-
-pub(crate) struct BoardBuilder {
-    pub resources: crate::resources::ResourcesBuilder,
-    pub rows: crate::board::rows::rows::RowsBuilder,
-    pub aristocrats: Vec<usize>,
-}
-
-impl BoardBuilder {
-    fn new(board: &Board) -> Self {
-        Self {
-            resources: board.resources.to_builder(),
-            rows: board.rows.to_builder(),
-            aristocrats: board.aristocrats.clone(),
-        }
-    }
-
-    pub fn build(self) -> Board {
-        Board {
-            resources: self.resources.build(),
-            rows: self.rows.build(),
-            aristocrats: self.aristocrats,
-        }
-    }
-}
\ No newline at end of file
@@ -1,5 +1,7 @@
 use crate::board::board::Board;
 use crate::card::card::Card;
+use crate::deck::Deck;
+use crate::error::SplendorError;
 
 pub(crate) struct CardReference {
     row_index: u8,
@@ -16,18 +18,16 @@ impl CardReference {
 }
 
 impl CardReference {
-    pub fn get_from_board(&self, board: &Board) -> &'static Card {
-        board.get_rows().get_row(self.row_index).get_card(self.card_index)
+    pub fn get_from_board(&self, board: &Board, deck: &'static Deck) -> Result<&'static Card, SplendorError> {
+        let card_index = board.get_rows().get_row(self.row_index)?.get_card_index(self.card_index).ok_or(self.to_error())?;
+        Ok(deck.get_card(card_index))
     }
     
     pub fn is_in_board(&self, board: &Board) -> bool {
-        board.get_rows().get_row(self.row_index).has_card(self.card_index)
+        board.get_rows().get_row(self.row_index).is_ok_and(|row| row.has_card(self.card_index))
     }
-    
-    pub fn get_row_index(&self) -> u8 {
-        self.row_index
-    }
-    pub fn get_card_index(&self) -> usize {
-        self.card_index
+
+    pub(crate) fn to_error(&self) -> SplendorError {
+        SplendorError::InvalidCardPosition { row_index: self.row_index, position: self.card_index }
     }
 }
\ No newline at end of file
@@ -1,17 +1,18 @@
-use crate::card::card::Card;
-use crate::card::card_storage::CARD_STORAGE;
+use serde::{Deserialize, Serialize};
 
-const CARD_COUNT: usize = 4;
+/// Visible card slots of a row
+pub const CARDS_PER_ROW: usize = 4;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Row {
-    visible: Vec<usize>,  // Indices into CARD_STORAGE
-    hidden: Vec<usize>,   // Indices into CARD_STORAGE
+    pub(crate) visible: Vec<Option<usize>>,  // One slot per position, ids into the deck of the rules
+    pub(crate) hidden: Vec<usize>,   // Ids into the deck of the rules
 }
 
 impl Row {
     pub fn new(mut card_indices: Vec<usize>) -> Row {
-        let visible: Vec<usize> = card_indices.drain(0..CARD_COUNT.min(card_indices.len())).collect();
+        let mut visible: Vec<Option<usize>> = card_indices.drain(0..CARDS_PER_ROW.min(card_indices.len())).map(Some).collect();
+        visible.resize(CARDS_PER_ROW, None);
         Row {
             visible,
             hidden: card_indices,
@@ -19,59 +20,16 @@ impl Row {
     }
 
     pub fn has_card(&self, index: usize) -> bool {
-        index < self.visible.len()
+        self.get_card_index(index).is_some()
     }
     
-    pub fn get_card(&self, index: usize) -> &'static Card {
-        CARD_STORAGE.get_card(self.visible[index])
+    /// Storage id of the visible card at the position
+    pub fn get_card_index(&self, index: usize) -> Option<usize> {
+        self.visible.get(index).copied().flatten()
     }
     
-    pub fn to_builder(&self) -> RowBuilder {
-        RowBuilder::new(self)
-    }
-    
-    pub fn get_hidden(&self) -> Vec<&'static Card> {
-        self.hidden.iter().map(|&i| CARD_STORAGE.get_card(i)).collect()
+    /// Storage ids of the hidden cards, the top one first
+    pub fn get_hidden(&self) -> &Vec<usize> {
+        &self.hidden
     }
 }
-
-pub(crate) struct RowBuilder {
-    visible: Vec<usize>,
-    hidden: Vec<usize>,
-}
-
-impl RowBuilder {
-    pub(crate) fn new(row: &Row) -> Self {
-        Self {
-            visible: row.visible.clone(),
-            hidden: row.hidden.clone(),
-        }
-    }
-
-    pub fn remove(&mut self, index: usize) -> &'static Card {
-        let card_index = self.visible.remove(index);
-        if self.hidden.is_empty() {
-            if !self.visible.is_empty() {
-                self.visible.pop();
-            }
-        } else {
-            self.visible.push(self.hidden.remove(0));
-        }
-        CARD_STORAGE.get_card(card_index)
-    }
-    
-    pub fn remove_from_hidden(&mut self) -> &'static Card {
-        if self.hidden.is_empty() {
-            panic!("No hidden cards to remove");
-        }
-        let card_index = self.hidden.remove(0);
-        CARD_STORAGE.get_card(card_index)
-    }
-
-    pub fn build(self) -> Row {
-        Row {
-            visible: self.visible,
-            hidden: self.hidden,
-        }
-    }
-}
\ No newline at end of file
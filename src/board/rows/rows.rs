@@ -1,22 +1,31 @@
+use serde::{Deserialize, Serialize};
 use rand::prelude::SliceRandom;
 use rand::Rng;
 use crate::board::rows::row::Row;
 use crate::card::tier::Tier;
-use crate::card::card_storage::CARD_STORAGE;
+use crate::deck::Deck;
+use crate::error::SplendorError;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
-#[derive(Clone)]
+fn get_tier(index: u8) -> Result<Tier, SplendorError> {
+    Tier::ALL.get(index as usize).copied().ok_or(SplendorError::InvalidRowIndex(index))
+}
+
+/// Saved as the list of rows from the first tier to the third
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "[Row; 3]", from = "[Row; 3]")]
 pub struct Rows {
     rows: HashMap<Tier, Row>,
 }
 
 impl Rows {
-    pub fn new<R: Rng>(rng: &mut R) -> Rows {
+    pub fn new<R: Rng>(deck: &Deck, rng: &mut R) -> Rows {
         let mut rows = HashMap::new();
         
         // Get indices for each tier and shuffle them
-        for tier in [Tier::First, Tier::Second, Tier::Third] {
-            let mut tier_indices = CARD_STORAGE.get_tier_indices(tier);
+        for tier in Tier::ALL {
+            let mut tier_indices = deck.get_card_storage().get_tier_indices(tier);
             tier_indices.shuffle(rng);
             rows.insert(tier, Row::new(tier_indices));
         }
@@ -24,47 +33,29 @@ impl Rows {
         Rows { rows }
     }
     
-    pub fn get_row(&self, index: u8) -> &Row {
-        match index {
-            0 => self.rows.get(&Tier::First).expect("First tier row not found"),
-            1 => self.rows.get(&Tier::Second).expect("Second tier row not found"),
-            2 => self.rows.get(&Tier::Third).expect("Third tier row not found"),
-            _ => panic!("Invalid index"),
-        }
-    }
-    
-    pub fn to_builder(&self) -> RowsBuilder {
-        RowsBuilder::new(self)
+    /// Rows of the first, second and third tier
+    pub(crate) fn from_rows(rows: [Row; 3]) -> Rows {
+        let [first, second, third] = rows;
+        Rows { rows: HashMap::from([(Tier::First, first), (Tier::Second, second), (Tier::Third, third)]) }
     }
-}
-
-pub(crate) struct RowsBuilder {
-    pub rows: HashMap<Tier, crate::board::rows::row::RowBuilder>,
-}
 
-impl RowsBuilder {
-    pub(crate) fn new(rows: &Rows) -> Self {
-        let mut builder_rows = HashMap::new();
-        for (tier, row) in &rows.rows {
-            builder_rows.insert(*tier, row.to_builder());
-        }
-        Self { rows: builder_rows }
+    pub fn get_row(&self, index: u8) -> Result<&Row, SplendorError> {
+        let tier = get_tier(index)?;
+        self.rows.get(&tier).ok_or(SplendorError::InvalidRowIndex(index))
     }
     
-    pub fn get(&mut self, index: u8) -> &mut crate::board::rows::row::RowBuilder {
-        match index {
-            0 => self.rows.get_mut(&Tier::First).expect("First tier row not found"),
-            1 => self.rows.get_mut(&Tier::Second).expect("Second tier row not found"),
-            2 => self.rows.get_mut(&Tier::Third).expect("Third tier row not found"),
-            _ => panic!("Invalid index"),
-        }
+    pub(crate) fn get_row_mut(&mut self, index: u8) -> Result<&mut Row, SplendorError> {
+        let tier = get_tier(index)?;
+        self.rows.get_mut(&tier).ok_or(SplendorError::InvalidRowIndex(index))
     }
 
-    pub fn build(self) -> Rows {
-        let mut rows = HashMap::new();
-        for (tier, row_builder) in self.rows {
-            rows.insert(tier, row_builder.build());
+}
+
+/// Hashes the rows in tier order, which a `HashMap` does not guarantee
+impl Hash for Rows {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for index in 0..3 {
+            self.get_row(index).ok().hash(state);
         }
-        Rows { rows }
     }
-}
\ No newline at end of file
+}
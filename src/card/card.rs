@@ -2,6 +2,7 @@ use crate::card::cost::Cost;
 use crate::card::tier::Tier;
 use crate::resource::Resource;
 
+#[derive(PartialEq)]
 pub struct Card {
     cost: Cost,
     production: Resource,
@@ -1,6 +1,6 @@
 use crate::resources::Resources;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Cost {
     n_green: u8,
     n_red: u8,
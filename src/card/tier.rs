@@ -3,4 +3,22 @@ pub enum Tier {
     First,
     Second,
     Third,
-}
\ No newline at end of file
+}
+impl Tier {
+    /// Tiers in row order
+    pub const ALL: [Tier; 3] = [Tier::First, Tier::Second, Tier::Third];
+
+    /// Index of the row of the tier, 0 for the first tier
+    pub fn index(&self) -> usize {
+        match self {
+            Tier::First => 0,
+            Tier::Second => 1,
+            Tier::Third => 2,
+        }
+    }
+
+    /// 1 for the first tier up to 3 for the third
+    pub fn number(&self) -> u8 {
+        self.index() as u8 + 1
+    }
+}
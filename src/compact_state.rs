@@ -0,0 +1,304 @@
+use crate::board::board::Board;
+use crate::board::rows::row::{Row, CARDS_PER_ROW};
+use crate::board::rows::rows::Rows;
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
+use crate::player::Player;
+use crate::resources::Resources;
+use crate::rule_set::{RuleSet, MAX_ARISTOCRATS, MAX_RESERVE_CARDS};
+
+const MAX_PLAYERS: usize = 4;
+const N_ROWS: usize = 3;
+/// Limit on the hidden cards of a row, above the 36 left in the largest tier of the official deck.
+/// States of decks with larger tiers are refused
+const MAX_HIDDEN_CARDS: usize = 40;
+/// Marks an empty card slot
+const NO_CARD: u8 = u8::MAX;
+
+/// Tokens in `Resources::new` order: green, red, blue, white, black, gold
+type Tokens = [u8; 6];
+
+fn to_tokens(resources: &Resources) -> Tokens {
+    [resources.n_green(), resources.n_red(), resources.n_blue(), resources.n_white(), resources.n_black(), resources.n_gold()]
+}
+
+fn from_tokens(tokens: &Tokens) -> Resources {
+    Resources::new(tokens[0], tokens[1], tokens[2], tokens[3], tokens[4], tokens[5])
+}
+
+fn to_card_id(card_index: usize) -> Result<u8, SplendorError> {
+    u8::try_from(card_index)
+        .ok()
+        .filter(|&id| id != NO_CARD)
+        .ok_or(SplendorError::TooLargeForCompactState("card ids must fit a byte"))
+}
+
+/// A player with owned cards and aristocrats as bitsets, production and points cached
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompactPlayer {
+    tokens: Tokens,
+    owned_cards: u128,
+    reserve: [u8; MAX_RESERVE_CARDS as usize],
+    /// Bit `i` is set when reserve slot `i` was reserved face down
+    reserved_blind: u8,
+    aristocrats: u16,
+    production: [u8; 5],
+    points: u8,
+}
+
+impl CompactPlayer {
+    const EMPTY: Self = Self {
+        tokens: [0; 6],
+        owned_cards: 0,
+        reserve: [NO_CARD; MAX_RESERVE_CARDS as usize],
+        reserved_blind: 0,
+        aristocrats: 0,
+        production: [0; 5],
+        points: 0,
+    };
+
+    fn new(player: &Player) -> Result<Self, SplendorError> {
+        if player.reserve.len() > MAX_RESERVE_CARDS as usize {
+            return Err(SplendorError::TooLargeForCompactState("too many reserved cards"));
+        }
+        let mut owned_cards = 0u128;
+        for &card_index in &player.deck {
+            let id = to_card_id(card_index)?;
+            if id >= 128 {
+                return Err(SplendorError::TooLargeForCompactState("owned card ids must be below 128"));
+            }
+            owned_cards |= 1 << id;
+        }
+        let mut reserve = [NO_CARD; MAX_RESERVE_CARDS as usize];
+        for (slot, &card_index) in reserve.iter_mut().zip(&player.reserve) {
+            *slot = to_card_id(card_index)?;
+        }
+        let mut aristocrats = 0u16;
+        for &index in &player.aristocrats {
+            if index >= 16 {
+                return Err(SplendorError::TooLargeForCompactState("aristocrat ids must be below 16"));
+            }
+            aristocrats |= 1 << index;
+        }
+        let production = player.get_production();
+        Ok(Self {
+            tokens: to_tokens(&player.resources),
+            owned_cards,
+            reserve,
+            reserved_blind: (0..player.reserve.len()).filter(|&index| player.is_reserved_blind(index)).fold(0, |mask, index| mask | 1 << index),
+            aristocrats,
+            production: [production.n_green(), production.n_red(), production.n_blue(), production.n_white(), production.n_black()],
+            points: player.get_points(),
+        })
+    }
+
+    /// Owned cards come back ordered by id, the order they were bought in is not kept
+    fn to_player(self) -> Player {
+        Player {
+            deck: (0..128).filter(|id| self.owned_cards >> id & 1 == 1).collect(),
+            resources: from_tokens(&self.tokens),
+            reserve: self.reserve.iter().take_while(|&&id| id != NO_CARD).map(|&id| id as usize).collect(),
+            reserved_blind: (0..self.get_n_reserved()).map(|index| self.reserved_blind >> index & 1 == 1).collect(),
+            aristocrats: (0..16).filter(|index| self.aristocrats >> index & 1 == 1).collect(),
+            production: self.get_production(),
+            points: self.points,
+        }
+    }
+
+    pub fn get_resources(&self) -> Resources {
+        from_tokens(&self.tokens)
+    }
+
+    pub fn get_production(&self) -> Resources {
+        let [green, red, blue, white, black] = self.production;
+        Resources::new(green, red, blue, white, black, 0)
+    }
+
+    pub fn get_points(&self) -> u8 {
+        self.points
+    }
+
+    pub fn owns_card(&self, card_index: usize) -> bool {
+        card_index < 128 && self.owned_cards >> card_index & 1 == 1
+    }
+
+    pub fn get_n_reserved(&self) -> usize {
+        self.reserve.iter().take_while(|&&id| id != NO_CARD).count()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct CompactRow {
+    visible: [u8; CARDS_PER_ROW],
+    hidden: [u8; MAX_HIDDEN_CARDS],
+    n_hidden: u8,
+}
+
+impl CompactRow {
+    fn new(row: &Row) -> Result<Self, SplendorError> {
+        if row.visible.len() > CARDS_PER_ROW || row.hidden.len() > MAX_HIDDEN_CARDS {
+            return Err(SplendorError::TooLargeForCompactState("too many cards in a row"));
+        }
+        let mut visible = [NO_CARD; CARDS_PER_ROW];
+        for (slot, card_index) in visible.iter_mut().zip(&row.visible) {
+            if let Some(card_index) = card_index {
+                *slot = to_card_id(*card_index)?;
+            }
+        }
+        let mut hidden = [NO_CARD; MAX_HIDDEN_CARDS];
+        for (slot, &card_index) in hidden.iter_mut().zip(&row.hidden) {
+            *slot = to_card_id(card_index)?;
+        }
+        Ok(Self { visible, hidden, n_hidden: row.hidden.len() as u8 })
+    }
+
+    fn to_row(self) -> Row {
+        Row {
+            visible: self.visible.iter().map(|&id| (id != NO_CARD).then_some(id as usize)).collect(),
+            hidden: self.hidden[..self.n_hidden as usize].iter().map(|&id| id as usize).collect(),
+        }
+    }
+}
+
+/// Fixed-size copy of a `GameState` that needs no allocation to clone
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompactState {
+    players: [CompactPlayer; MAX_PLAYERS],
+    n_players: u8,
+    current_player_index: u8,
+    phase: TurnPhase,
+    n_consecutive_passes: u8,
+    payment: Tokens,
+    bank: Tokens,
+    rows: [CompactRow; N_ROWS],
+    aristocrats: [u8; MAX_ARISTOCRATS as usize],
+    n_aristocrats: u8,
+    rules: RuleSet,
+}
+
+impl CompactState {
+    pub fn new(game_state: &GameState) -> Result<Self, SplendorError> {
+        if game_state.players.len() > MAX_PLAYERS {
+            return Err(SplendorError::TooLargeForCompactState("too many players"));
+        }
+        if game_state.board.aristocrats.len() > MAX_ARISTOCRATS as usize {
+            return Err(SplendorError::TooLargeForCompactState("too many aristocrats"));
+        }
+        let mut players = [CompactPlayer::EMPTY; MAX_PLAYERS];
+        for (slot, player) in players.iter_mut().zip(&game_state.players) {
+            *slot = CompactPlayer::new(player)?;
+        }
+        let mut rows = [CompactRow { visible: [NO_CARD; CARDS_PER_ROW], hidden: [NO_CARD; MAX_HIDDEN_CARDS], n_hidden: 0 }; N_ROWS];
+        for (index, slot) in rows.iter_mut().enumerate() {
+            *slot = CompactRow::new(game_state.board.rows.get_row(index as u8)?)?;
+        }
+        let mut aristocrats = [0u8; MAX_ARISTOCRATS as usize];
+        for (slot, &index) in aristocrats.iter_mut().zip(&game_state.board.aristocrats) {
+            *slot = index as u8;
+        }
+        Ok(Self {
+            players,
+            n_players: game_state.players.len() as u8,
+            current_player_index: game_state.current_player_index as u8,
+            phase: game_state.phase,
+            n_consecutive_passes: game_state.n_consecutive_passes,
+            payment: to_tokens(&game_state.payment),
+            bank: to_tokens(&game_state.board.resources),
+            rows,
+            aristocrats,
+            n_aristocrats: game_state.board.aristocrats.len() as u8,
+            rules: game_state.rules,
+        })
+    }
+
+    pub fn to_game_state(&self) -> GameState {
+        let [first, second, third] = self.rows.map(CompactRow::to_row);
+        GameState {
+            players: self.get_players().iter().map(|player| player.to_player()).collect(),
+            current_player_index: self.current_player_index as usize,
+            board: Board {
+                resources: from_tokens(&self.bank),
+                rows: Rows::from_rows([first, second, third]),
+                aristocrats: self.aristocrats[..self.n_aristocrats as usize].iter().map(|&index| index as usize).collect(),
+            },
+            phase: self.phase,
+            n_consecutive_passes: self.n_consecutive_passes,
+            payment: from_tokens(&self.payment),
+            rules: self.rules,
+            zobrist_hash: 0,
+        }
+        .with_zobrist_hash()
+    }
+
+    pub fn get_players(&self) -> &[CompactPlayer] {
+        &self.players[..self.n_players as usize]
+    }
+
+    pub fn get_current_player_index(&self) -> usize {
+        self.current_player_index as usize
+    }
+
+    pub fn get_phase(&self) -> TurnPhase {
+        self.phase
+    }
+
+    pub fn get_bank(&self) -> Resources {
+        from_tokens(&self.bank)
+    }
+
+    pub fn get_rules(&self) -> &RuleSet {
+        &self.rules
+    }
+}
+
+impl GameState {
+    pub fn to_compact(&self) -> Result<CompactState, SplendorError> {
+        CompactState::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::create_initial_game_state;
+    use crate::testing::random_games;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn is_too_large(result: Result<CompactState, SplendorError>) -> bool {
+        matches!(result, Err(SplendorError::TooLargeForCompactState(_)))
+    }
+
+    #[test]
+    fn states_round_trip() {
+        for record in random_games(6) {
+            for state in record.states() {
+                let restored = CompactState::new(&state).unwrap().to_game_state();
+                assert!(restored == state);
+                assert_eq!(restored.get_zobrist_hash(), state.get_zobrist_hash());
+            }
+        }
+    }
+
+    /// A deck larger than the official one can hand out ids the compact layout cannot hold
+    #[test]
+    fn ids_of_large_decks_are_refused() {
+        let initial_state = create_initial_game_state(RuleSet::official(2).unwrap(), &mut ChaCha8Rng::seed_from_u64(0));
+
+        let mut owned_card = initial_state.clone();
+        owned_card.players[0].deck.push(128);
+
+        let mut reserved_card = initial_state.clone();
+        reserved_card.players[0].insert_into_reserve(0, NO_CARD as usize, false);
+
+        let mut aristocrat = initial_state.clone();
+        aristocrat.players[0].add_aristocrat(16);
+
+        let mut hidden_cards = initial_state.clone();
+        hidden_cards.board.rows.get_row_mut(0).unwrap().hidden.resize(MAX_HIDDEN_CARDS + 1, 0);
+
+        for state in [owned_card, reserved_card, aristocrat, hidden_cards] {
+            assert!(is_too_large(state.to_compact()));
+        }
+    }
+}
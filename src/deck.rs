@@ -0,0 +1,311 @@
+//! Cards and aristocrats games are played with, read from JSON such as `data/deck.json`:
+//!
+//! ```json
+//! {
+//!   "cards": [{"tier": 1, "colour": "Black", "points": 0, "cost": {"green": 1, "red": 1, "blue": 1, "white": 1}}],
+//!   "aristocrats": [{"cost": {"red": 4, "black": 4}}]
+//! }
+//! ```
+//!
+//! Colours left out of a cost are free. A card or aristocrat id is its position in the file.
+//!
+//! Each game is played with the deck of its `RuleSet`. Decks are registered for the life of the
+//! process so that rule sets can share them, and saved states, notations and records name their deck
+//! by its hash: they are only read back once that deck is registered.
+
+use crate::aristocrat::Aristocrat;
+use crate::aristocrat_storage::AristocratStorage;
+use crate::board::rows::row::CARDS_PER_ROW;
+use crate::card::card::Card;
+use crate::card::card_storage::CardStorage;
+use crate::card::cost::Cost;
+use crate::card::tier::Tier;
+use crate::error::SplendorError;
+use crate::resource::Resource;
+use crate::rule_set::MAX_ARISTOCRATS;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+/// The official deck, embedded at compile time
+const OFFICIAL_DECK: &str = include_str!("../data/deck.json");
+
+/// Every deck registered so far, each leaked once so that rule sets can hold it and stay `Copy`
+static DECKS: Lazy<Mutex<Vec<&'static Deck>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+static OFFICIAL: Lazy<&'static Deck> = Lazy::new(|| Deck::from_json(OFFICIAL_DECK).expect("The embedded deck is valid").register());
+
+/// 64-bit FNV-1a, stable across builds unlike the standard library hasher
+fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn deck_error(reason: impl Into<String>) -> SplendorError {
+    SplendorError::InvalidDeck(reason.into())
+}
+
+fn is_free(n_tokens: &u8) -> bool {
+    *n_tokens == 0
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CostRecord {
+    #[serde(default, skip_serializing_if = "is_free")]
+    green: u8,
+    #[serde(default, skip_serializing_if = "is_free")]
+    red: u8,
+    #[serde(default, skip_serializing_if = "is_free")]
+    blue: u8,
+    #[serde(default, skip_serializing_if = "is_free")]
+    white: u8,
+    #[serde(default, skip_serializing_if = "is_free")]
+    black: u8,
+}
+
+impl CostRecord {
+    fn new(cost: &Cost) -> Self {
+        Self { green: cost.n_green(), red: cost.n_red(), blue: cost.n_blue(), white: cost.n_white(), black: cost.n_black() }
+    }
+
+    fn to_cost(&self) -> Cost {
+        Cost::new(self.green, self.red, self.blue, self.white, self.black)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CardRecord {
+    tier: u8,
+    colour: Resource,
+    points: u8,
+    cost: CostRecord,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AristocratRecord {
+    cost: CostRecord,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeckRecord {
+    cards: Vec<CardRecord>,
+    aristocrats: Vec<AristocratRecord>,
+}
+
+/// Cards and aristocrats of a game, checked to be playable. Decks with the same hash are the same
+pub struct Deck {
+    cards: CardStorage,
+    aristocrats: AristocratStorage,
+    hash: u64,
+}
+
+impl Deck {
+    pub fn official() -> &'static Deck {
+        *OFFICIAL
+    }
+
+    /// Every tier needs enough cards to fill its row and there must be aristocrats for any rule set.
+    /// Costs cannot be free and no two cards or aristocrats can be the same
+    pub fn from_json(json: &str) -> Result<Self, SplendorError> {
+        let record: DeckRecord = serde_json::from_str(json).map_err(|error| deck_error(error.to_string()))?;
+        let mut cards: Vec<Card> = Vec::new();
+        for (index, card) in record.cards.iter().enumerate() {
+            let tier = *Tier::ALL
+                .get((card.tier as usize).wrapping_sub(1))
+                .ok_or_else(|| deck_error(format!("card {} has tier {}, expected 1, 2 or 3", index, card.tier)))?;
+            let new_card = Card::new(card.cost.to_cost(), card.colour, card.points, tier);
+            if new_card.cost().to_resources().sum() == 0 {
+                return Err(deck_error(format!("card {} costs nothing", index)));
+            }
+            if let Some(same) = cards.iter().position(|other| *other == new_card) {
+                return Err(deck_error(format!("cards {} and {} are the same", same, index)));
+            }
+            cards.push(new_card);
+        }
+        for tier in Tier::ALL {
+            let n_cards = cards.iter().filter(|card| card.tier() == tier).count();
+            if n_cards < CARDS_PER_ROW {
+                return Err(deck_error(format!("tier {} has {} cards, at least {} are needed", tier.number(), n_cards, CARDS_PER_ROW)));
+            }
+        }
+        let mut aristocrats: Vec<Aristocrat> = Vec::new();
+        for (index, aristocrat) in record.aristocrats.iter().enumerate() {
+            let new_aristocrat = Aristocrat::new(aristocrat.cost.to_cost());
+            if new_aristocrat.cost().to_resources().sum() == 0 {
+                return Err(deck_error(format!("aristocrat {} costs nothing", index)));
+            }
+            if let Some(same) = aristocrats.iter().position(|other| *other == new_aristocrat) {
+                return Err(deck_error(format!("aristocrats {} and {} are the same", same, index)));
+            }
+            aristocrats.push(new_aristocrat);
+        }
+        if aristocrats.len() < MAX_ARISTOCRATS as usize {
+            return Err(deck_error(format!("{} aristocrats given, at least {} are needed", aristocrats.len(), MAX_ARISTOCRATS)));
+        }
+        let mut deck = Self { cards: CardStorage::new(cards), aristocrats: AristocratStorage::new(aristocrats), hash: 0 };
+        deck.hash = fingerprint(deck.to_json().as_bytes());
+        Ok(deck)
+    }
+
+    pub fn to_json(&self) -> String {
+        let record = DeckRecord {
+            cards: self.cards
+                .iter()
+                .map(|card| CardRecord { tier: card.tier().number(), colour: card.production(), points: card.n_points(), cost: CostRecord::new(card.cost()) })
+                .collect(),
+            aristocrats: self.aristocrats.iter().map(|aristocrat| AristocratRecord { cost: CostRecord::new(aristocrat.cost()) }).collect(),
+        };
+        serde_json::to_string_pretty(&record).expect("Decks always serialize")
+    }
+
+    /// The copy shared by every game played with this deck, registering it the first time.
+    /// Saved states, notations and records naming its hash can be read from then on
+    pub fn register(self) -> &'static Deck {
+        let mut decks = DECKS.lock().expect("No thread panics while registering a deck");
+        if let Some(&deck) = decks.iter().find(|deck| deck.hash == self.hash) {
+            return deck;
+        }
+        let deck: &'static Deck = Box::leak(Box::new(self));
+        decks.push(deck);
+        deck
+    }
+
+    /// The registered deck with this hash
+    pub fn find(hash: u64) -> Option<&'static Deck> {
+        Lazy::force(&OFFICIAL);
+        DECKS.lock().expect("No thread panics while registering a deck").iter().find(|deck| deck.hash == hash).copied()
+    }
+
+    /// Fingerprint of the cards and aristocrats, saved with states and records so that they are
+    /// only loaded with the deck their ids refer to
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn n_cards(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn n_aristocrats(&self) -> usize {
+        self.aristocrats.len()
+    }
+
+    pub fn get_card(&self, index: usize) -> &Card {
+        self.cards.get_card(index)
+    }
+
+    pub fn get_aristocrat(&self, index: usize) -> &Aristocrat {
+        self.aristocrats.get_aristocrat(index)
+    }
+
+    pub(crate) fn get_card_storage(&self) -> &CardStorage {
+        &self.cards
+    }
+}
+
+impl PartialEq for Deck {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl Eq for Deck {}
+
+impl Hash for Deck {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+impl fmt::Debug for Deck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Deck({:016x})", self.hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn official_json() -> Value {
+        serde_json::from_str(&Deck::official().to_json()).unwrap()
+    }
+
+    fn is_deck_error(json: &Value) -> bool {
+        matches!(Deck::from_json(&json.to_string()), Err(SplendorError::InvalidDeck(_)))
+    }
+
+    #[test]
+    fn decks_round_trip() {
+        let deck = Deck::from_json(&Deck::official().to_json()).unwrap();
+        assert_eq!(deck.to_json(), Deck::official().to_json());
+        assert_eq!(deck.get_hash(), 0xf04d_c4c0_472d_0186);
+        assert_eq!((deck.n_cards(), deck.n_aristocrats()), (90, 10));
+    }
+
+    #[test]
+    fn repeated_cards_and_aristocrats_are_rejected() {
+        let mut json = official_json();
+        let card = json["cards"][0].clone();
+        json["cards"].as_array_mut().unwrap().push(card);
+        assert!(is_deck_error(&json));
+
+        let mut json = official_json();
+        let aristocrat = json["aristocrats"][0].clone();
+        json["aristocrats"].as_array_mut().unwrap().push(aristocrat);
+        assert!(is_deck_error(&json));
+    }
+
+    #[test]
+    fn tiers_must_fill_their_row() {
+        let mut json = official_json();
+        let mut n_third_tier = 0;
+        json["cards"].as_array_mut().unwrap().retain(|card| {
+            n_third_tier += (card["tier"] == 3) as usize;
+            card["tier"] != 3 || n_third_tier < CARDS_PER_ROW
+        });
+        assert!(is_deck_error(&json));
+    }
+
+    #[test]
+    fn unknown_tiers_are_rejected() {
+        for tier in [0, 4] {
+            let mut json = official_json();
+            json["cards"][0]["tier"] = tier.into();
+            assert!(is_deck_error(&json));
+        }
+    }
+
+    #[test]
+    fn free_costs_and_missing_aristocrats_are_rejected() {
+        let mut json = official_json();
+        json["cards"][0]["cost"] = json!({});
+        assert!(is_deck_error(&json));
+
+        let mut json = official_json();
+        json["aristocrats"].as_array_mut().unwrap().truncate(MAX_ARISTOCRATS as usize - 1);
+        assert!(is_deck_error(&json));
+
+        let mut json = official_json();
+        json["cards"][0]["cost"]["gold"] = 1.into();
+        assert!(is_deck_error(&json));
+    }
+
+    #[test]
+    fn registered_decks_are_shared() {
+        let mut json = official_json();
+        json["aristocrats"].as_array_mut().unwrap().pop();
+        let deck = Deck::from_json(&json.to_string()).unwrap().register();
+        let again = Deck::from_json(&json.to_string()).unwrap().register();
+        assert!(std::ptr::eq(deck, again));
+        assert!(Deck::find(deck.get_hash()).is_some_and(|found| std::ptr::eq(found, deck)));
+        assert!(Deck::find(Deck::official().get_hash()).is_some());
+        assert!(Deck::find(deck.get_hash() ^ Deck::official().get_hash()).is_none());
+    }
+}
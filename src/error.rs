@@ -0,0 +1,85 @@
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyValueError};
+use pyo3::PyErr;
+use std::fmt;
+
+/// Everything that can go wrong when setting up or playing a game
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SplendorError {
+    /// Only 2, 3 or 4 players are supported
+    InvalidPlayerCount(u8),
+    /// A rule parameter the engine cannot play with
+    InvalidRule(String),
+    /// Rows are indexed 0 to 2
+    InvalidRowIndex(u8),
+    /// No visible card at this position of the row
+    InvalidCardPosition { row_index: u8, position: usize },
+    /// No hidden cards are left in the row
+    EmptyDeck(u8),
+    /// No player with this index
+    InvalidPlayerIndex(usize),
+    /// No move with this index
+    InvalidMoveIndex(usize),
+    /// The move cannot be played in the current state
+    InvalidMove,
+    /// No move of the table has the fields of this action
+    InvalidAction(String),
+    /// The move does not draw this card from the hidden cards
+    InvalidChanceOutcome,
+    /// The state does not fit the fixed-size compact representation
+    TooLargeForCompactState(&'static str),
+    /// A saved state that cannot be read back
+    InvalidSavedState(String),
+    /// Text that does not follow the position or move notation
+    InvalidNotation(String),
+    /// A game record that cannot be read back or replayed
+    InvalidGameRecord(String),
+    /// A deck definition that cannot be played with
+    InvalidDeck(String),
+}
+
+impl fmt::Display for SplendorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplendorError::InvalidPlayerCount(n_players) => write!(f, "N players must be 2, 3 or 4, got {}", n_players),
+            SplendorError::InvalidRule(reason) => write!(f, "Invalid rule: {}", reason),
+            SplendorError::InvalidRowIndex(row_index) => write!(f, "Row index {} out of range", row_index),
+            SplendorError::InvalidCardPosition { row_index, position } => write!(f, "No card at position {} of row {}", position, row_index),
+            SplendorError::EmptyDeck(row_index) => write!(f, "No hidden cards left in row {}", row_index),
+            SplendorError::InvalidPlayerIndex(index) => write!(f, "Player index {} out of range", index),
+            SplendorError::InvalidMoveIndex(index) => write!(f, "Move index {} out of range", index),
+            SplendorError::InvalidMove => write!(f, "Invalid move for current game state"),
+            SplendorError::InvalidAction(action) => write!(f, "Action '{}' is not in the move table", action),
+            SplendorError::InvalidChanceOutcome => write!(f, "Chance outcome cannot follow this move"),
+            SplendorError::TooLargeForCompactState(reason) => write!(f, "State too large for the compact representation: {}", reason),
+            SplendorError::InvalidSavedState(reason) => write!(f, "Invalid saved state: {}", reason),
+            SplendorError::InvalidNotation(reason) => write!(f, "Invalid notation: {}", reason),
+            SplendorError::InvalidGameRecord(reason) => write!(f, "Invalid game record: {}", reason),
+            SplendorError::InvalidDeck(reason) => write!(f, "Invalid deck: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SplendorError {}
+
+impl From<SplendorError> for PyErr {
+    fn from(error: SplendorError) -> Self {
+        let message = error.to_string();
+        match error {
+            SplendorError::InvalidPlayerCount(_)
+            | SplendorError::InvalidRule(_)
+            | SplendorError::InvalidMove
+            | SplendorError::InvalidAction(_)
+            | SplendorError::InvalidChanceOutcome
+            | SplendorError::TooLargeForCompactState(_)
+            | SplendorError::InvalidSavedState(_)
+            | SplendorError::InvalidNotation(_)
+            | SplendorError::InvalidGameRecord(_)
+            | SplendorError::InvalidDeck(_) => PyValueError::new_err(message),
+            SplendorError::InvalidRowIndex(_)
+            | SplendorError::InvalidCardPosition { .. }
+            | SplendorError::InvalidPlayerIndex(_)
+            | SplendorError::InvalidMoveIndex(_) => PyIndexError::new_err(message),
+            SplendorError::EmptyDeck(_) => PyRuntimeError::new_err(message),
+        }
+    }
+}
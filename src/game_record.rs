@@ -0,0 +1,357 @@
+//! Text record of a whole game, a header of `[Key "value"]` lines followed by one move per line:
+//!
+//! ```text
+//! [Format "2"]
+//! [Seed "42"]
+//! [Deck "f04dc4c0472d0186"]
+//! [Players "2"]
+//! [Rules "15,10,3,4,5,3"]
+//! [Player0 "random"]
+//! [Player1 "random"]
+//! [Result "1"]
+//! [Start "G4R4B4W4K4Y5 19,23,15,2:4,13,… 0 action 0 - f04dc4c0472d0186"]
+//!
+//! take GBR
+//! res 3.2 @87
+//! ```
+//!
+//! Moves use the notation of `Action`. A card drawn in place of the top hidden card of its row is given
+//! after `@`. The start position uses the `notation` format, so a record replays the same whatever the
+//! random number generator; only without it is the game set up again from `Seed`.
+//! The deck is the hash of the cards and aristocrats ids refer to, a record is only read once that deck
+//! is registered.
+//! The result lists the winners, `*` while the game is not over.
+//! Several records can follow each other in one file, each starting with its `Format` line.
+
+use crate::error::SplendorError;
+use crate::game_state::{create_initial_game_state, GameResult, GameState};
+use crate::moves::action::Action;
+use crate::moves::chance::ChanceOutcome;
+use crate::notation::{format_rules, parse_rules};
+use crate::rule_set::RuleSet;
+use crate::serialization::find_deck;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt::Write;
+
+/// Bumped whenever the layout of a record changes
+pub const RECORD_FORMAT_VERSION: u32 = 2;
+
+const UNFINISHED: &str = "*";
+const UNKNOWN_NAME: &str = "?";
+const CHANCE_MARK: char = '@';
+
+fn record_error(reason: impl Into<String>) -> SplendorError {
+    SplendorError::InvalidGameRecord(reason.into())
+}
+
+fn format_result(game_state: &GameState) -> String {
+    match game_state.get_result() {
+        Some(result) => result.get_winners().iter().map(|winner| winner.to_string()).collect::<Vec<_>>().join(","),
+        None => UNFINISHED.to_string(),
+    }
+}
+
+fn parse_header(line: &str) -> Result<(&str, &str), SplendorError> {
+    line.strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .and_then(|line| line.split_once(' '))
+        .and_then(|(key, value)| Some((key, value.strip_prefix('"')?.strip_suffix('"')?)))
+        .ok_or_else(|| record_error(format!("'{}' is not a [Key \"value\"] header", line)))
+}
+
+/// A game from its initial state, with every move played since
+#[derive(Clone)]
+pub struct GameRecord {
+    seed: Option<u64>,
+    names: Vec<String>,
+    initial_state: GameState,
+    moves: Vec<(Action, Option<ChanceOutcome>)>,
+    final_state: GameState,
+}
+
+impl GameRecord {
+    /// A record without moves, the players named `?`
+    pub fn new(initial_state: GameState) -> Self {
+        let names = vec![UNKNOWN_NAME.to_string(); initial_state.get_players().len()];
+        Self { seed: None, names, final_state: initial_state.clone(), initial_state, moves: Vec::new() }
+    }
+
+    /// A game set up from a `ChaCha8Rng` seeded with `seed`
+    pub fn from_seed(rules: RuleSet, seed: u64) -> Self {
+        let initial_state = create_initial_game_state(rules, &mut ChaCha8Rng::seed_from_u64(seed));
+        Self::new(initial_state).with_seed(seed)
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Names of the players or the agents playing them, in player order
+    pub fn with_names(mut self, names: Vec<String>) -> Result<Self, SplendorError> {
+        if names.len() != self.names.len() {
+            return Err(record_error(format!("{} names given for {} players", names.len(), self.names.len())));
+        }
+        if let Some(name) = names.iter().find(|name| name.contains(['"', '\n', '\r'])) {
+            return Err(record_error(format!("name {:?} cannot hold quotes or line breaks", name)));
+        }
+        self.names = names;
+        Ok(self)
+    }
+
+    /// Plays the action on the last state and records it.
+    /// Without an outcome the top hidden card is drawn, as in `GameState::apply_with_chance`
+    pub fn push(&mut self, action: Action, outcome: Option<ChanceOutcome>) -> Result<(), SplendorError> {
+        self.final_state.apply_with_chance(action, outcome)?;
+        self.moves.push((action, outcome));
+        Ok(())
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn get_names(&self) -> &Vec<String> {
+        &self.names
+    }
+
+    pub fn get_initial_state(&self) -> &GameState {
+        &self.initial_state
+    }
+
+    pub fn get_moves(&self) -> &Vec<(Action, Option<ChanceOutcome>)> {
+        &self.moves
+    }
+
+    pub fn get_final_state(&self) -> &GameState {
+        &self.final_state
+    }
+
+    pub fn get_result(&self) -> Option<GameResult> {
+        self.final_state.get_result()
+    }
+
+    /// The initial state followed by the state after each move
+    pub fn states(&self) -> Vec<GameState> {
+        let mut game_state = self.initial_state.clone();
+        let mut states = vec![game_state.clone()];
+        for &(action, outcome) in &self.moves {
+            game_state.apply_with_chance(action, outcome).expect("Recorded moves were legal when pushed");
+            states.push(game_state.clone());
+        }
+        states
+    }
+
+    pub fn to_text(&self) -> String {
+        let rules = self.initial_state.get_rules();
+        let mut text = String::new();
+        writeln!(text, "[Format \"{}\"]", RECORD_FORMAT_VERSION).unwrap();
+        if let Some(seed) = self.seed {
+            writeln!(text, "[Seed \"{}\"]", seed).unwrap();
+        }
+        writeln!(text, "[Deck \"{:016x}\"]", rules.deck().get_hash()).unwrap();
+        writeln!(text, "[Players \"{}\"]", rules.n_players()).unwrap();
+        writeln!(text, "[Rules \"{}\"]", format_rules(rules)).unwrap();
+        for (index, name) in self.names.iter().enumerate() {
+            writeln!(text, "[Player{} \"{}\"]", index, name).unwrap();
+        }
+        writeln!(text, "[Result \"{}\"]", format_result(&self.final_state)).unwrap();
+        writeln!(text, "[Start \"{}\"]", self.initial_state.to_notation()).unwrap();
+        writeln!(text).unwrap();
+        for &(action, outcome) in &self.moves {
+            match outcome {
+                Some(outcome) => writeln!(text, "{} {}{}", action, CHANCE_MARK, outcome.get_card_index()).unwrap(),
+                None => writeln!(text, "{}", action).unwrap(),
+            }
+        }
+        text
+    }
+
+    /// Reads a single record, replaying every move
+    pub fn from_text(text: &str) -> Result<Self, SplendorError> {
+        let mut records = Self::read_all(text)?;
+        if records.len() != 1 {
+            return Err(record_error(format!("expected one game, found {}", records.len())));
+        }
+        Ok(records.remove(0))
+    }
+
+    /// Records one after another, separated by a blank line
+    pub fn write_all(records: &[GameRecord]) -> String {
+        records.iter().map(GameRecord::to_text).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Every record of a text written by `write_all`
+    pub fn read_all(text: &str) -> Result<Vec<Self>, SplendorError> {
+        let mut records = Vec::new();
+        let mut headers: Vec<(&str, &str)> = Vec::new();
+        let mut moves: Vec<&str> = Vec::new();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if !line.starts_with('[') {
+                if headers.is_empty() {
+                    return Err(record_error(format!("move '{}' comes before any header", line)));
+                }
+                moves.push(line);
+                continue;
+            }
+            let (key, value) = parse_header(line)?;
+            if key == "Format" && !headers.is_empty() {
+                records.push(Self::from_parts(&headers, &moves)?);
+                headers.clear();
+                moves.clear();
+            } else if !moves.is_empty() {
+                return Err(record_error(format!("header '{}' comes after the moves", line)));
+            }
+            headers.push((key, value));
+        }
+        if !headers.is_empty() {
+            records.push(Self::from_parts(&headers, &moves)?);
+        }
+        Ok(records)
+    }
+
+    fn from_parts(headers: &[(&str, &str)], moves: &[&str]) -> Result<Self, SplendorError> {
+        let header = |key: &str| headers.iter().find(|(header_key, _)| *header_key == key).map(|&(_, value)| value);
+        let number = |key: &str| -> Result<Option<u64>, SplendorError> {
+            header(key)
+                .map(|value| value.parse().map_err(|_| record_error(format!("{} '{}' is not a number", key, value))))
+                .transpose()
+        };
+        let version = number("Format")?.ok_or_else(|| record_error("missing Format header"))?;
+        if version != RECORD_FORMAT_VERSION as u64 {
+            return Err(record_error(format!("format version {} is not supported, expected {}", version, RECORD_FORMAT_VERSION)));
+        }
+        let seed = number("Seed")?;
+        let deck = header("Deck").ok_or_else(|| record_error("missing Deck header"))?;
+        let deck = u64::from_str_radix(deck, 16).map_err(|_| record_error(format!("Deck '{}' is not a hash", deck)))?;
+        let deck = find_deck(deck, SplendorError::InvalidGameRecord)?;
+        let n_players = number("Players")?.ok_or_else(|| record_error("missing Players header"))?;
+        let n_players = u8::try_from(n_players).map_err(|_| SplendorError::InvalidPlayerCount(u8::MAX))?;
+        let rules = match header("Rules") {
+            Some(rules) => parse_rules(rules, n_players)?,
+            None => RuleSet::official(n_players)?,
+        }
+        .with_deck(deck);
+        let initial_state = match (header("Start"), seed) {
+            (Some(start), _) => GameState::from_notation(start)?,
+            (None, Some(seed)) => create_initial_game_state(rules, &mut ChaCha8Rng::seed_from_u64(seed)),
+            (None, None) => return Err(record_error("either a Start or a Seed header is needed")),
+        };
+        if *initial_state.get_rules() != rules {
+            return Err(record_error("the start position is not played with the recorded rules"));
+        }
+        let names = (0..n_players)
+            .map(|index| header(&format!("Player{}", index)).unwrap_or(UNKNOWN_NAME).to_string())
+            .collect();
+        let mut record = Self { seed, ..Self::new(initial_state) }.with_names(names)?;
+        for (number, line) in moves.iter().enumerate() {
+            record
+                .push_text(line)
+                .map_err(|error| record_error(format!("move {} '{}': {}", number + 1, line, error)))?;
+        }
+        let result = header("Result").unwrap_or(UNFINISHED);
+        let replayed_result = format_result(&record.final_state);
+        if result != replayed_result {
+            return Err(record_error(format!("result '{}' does not match the replayed game, '{}'", result, replayed_result)));
+        }
+        Ok(record)
+    }
+
+    fn push_text(&mut self, line: &str) -> Result<(), SplendorError> {
+        let (action, card_index) = match line.split_once(CHANCE_MARK) {
+            Some((action, card_index)) => (action, Some(card_index.trim())),
+            None => (line, None),
+        };
+        let action: Action = action.parse()?;
+        let outcome = match card_index {
+            Some(card_index) => {
+                let card_index = card_index.parse().map_err(|_| record_error(format!("'{}' is not a card id", card_index)))?;
+                let row = self.final_state.get_draw_row(action).ok_or(SplendorError::InvalidChanceOutcome)?;
+                Some(ChanceOutcome::new(row, card_index))
+            }
+            None => None,
+        };
+        self.push(action, outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+    use crate::testing::{custom_deck, random_games};
+
+    fn is_record_error<T>(result: Result<T, SplendorError>) -> bool {
+        matches!(result, Err(SplendorError::InvalidGameRecord(_)))
+    }
+
+    #[test]
+    fn records_round_trip() {
+        for (seed, record) in random_games(6).enumerate() {
+            let n_players = record.get_names().len();
+            let record = record.with_names(vec!["first".to_string(); n_players]).unwrap();
+            let text = record.to_text();
+            assert!(text.lines().any(|line| line.contains(CHANCE_MARK)));
+            let read = GameRecord::from_text(&text).unwrap();
+            assert_eq!(read.to_text(), text);
+            assert_eq!(read.get_seed(), Some(seed as u64));
+            assert_eq!(read.get_names(), record.get_names());
+            assert_eq!(read.get_moves(), record.get_moves());
+            assert!(read.get_final_state() == record.get_final_state());
+            assert_eq!(read.get_result(), record.get_result());
+        }
+    }
+
+    #[test]
+    fn several_records_round_trip() {
+        let records: Vec<GameRecord> = random_games(3).collect();
+        let text = GameRecord::write_all(&records);
+        let read = GameRecord::read_all(&text).unwrap();
+        assert_eq!(read.len(), records.len());
+        assert_eq!(GameRecord::write_all(&read), text);
+        assert!(is_record_error(GameRecord::from_text(&text)));
+    }
+
+    #[test]
+    fn records_without_start_are_set_up_from_the_seed() {
+        let mut record = GameRecord::from_seed(RuleSet::official(2).unwrap(), 7);
+        let action = record.get_final_state().legal_actions()[0];
+        record.push(action, None).unwrap();
+        let text: String = record.to_text().lines().filter(|line| !line.starts_with("[Start")).map(|line| format!("{}\n", line)).collect();
+        let read = GameRecord::from_text(&text).unwrap();
+        assert!(read.get_final_state() == record.get_final_state());
+    }
+
+    #[test]
+    fn records_of_custom_decks_round_trip() {
+        let mut record = GameRecord::from_seed(RuleSet::official(2).unwrap().with_deck(custom_deck()), 7);
+        let action = record.get_final_state().legal_actions()[0];
+        record.push(action, None).unwrap();
+        let text = record.to_text();
+        assert!(text.contains(&format!("[Deck \"{:016x}\"]", custom_deck().get_hash())));
+        let read = GameRecord::from_text(&text).unwrap();
+        assert!(read.get_final_state() == record.get_final_state());
+        let without_start: String = text.lines().filter(|line| !line.starts_with("[Start")).map(|line| format!("{}\n", line)).collect();
+        assert!(GameRecord::from_text(&without_start).unwrap().get_final_state() == record.get_final_state());
+    }
+
+    #[test]
+    fn mismatched_records_are_rejected() {
+        let text = random_games(1).next().unwrap().to_text();
+        let result_line = text.lines().find(|line| line.starts_with("[Result")).unwrap();
+        let wrong_result = text.replace(result_line, "[Result \"*\"]");
+        assert!(is_record_error(GameRecord::from_text(&wrong_result)));
+        let other_version = text.replace(&format!("[Format \"{}\"]", RECORD_FORMAT_VERSION), &format!("[Format \"{}\"]", RECORD_FORMAT_VERSION + 1));
+        assert!(is_record_error(GameRecord::from_text(&other_version)));
+        let deck_line = text.lines().find(|line| line.starts_with("[Deck")).unwrap();
+        let unregistered_deck = text.replace(deck_line, &format!("[Deck \"{:016x}\"]", Deck::official().get_hash() ^ 1));
+        assert!(is_record_error(GameRecord::from_text(&unregistered_deck)));
+        let other_deck = text.replace(deck_line, &format!("[Deck \"{:016x}\"]", custom_deck().get_hash()));
+        assert!(is_record_error(GameRecord::from_text(&other_deck)));
+        let missing_deck = text.replace(&format!("{}\n", deck_line), "");
+        assert!(is_record_error(GameRecord::from_text(&missing_deck)));
+        let unknown_card = text.replacen(&format!("{}", CHANCE_MARK), &format!("{}999", CHANCE_MARK), 1);
+        assert!(GameRecord::from_text(&unknown_card).is_err());
+    }
+}
@@ -1,17 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use rand::Rng;
 use crate::board::board::Board;
 use crate::card::card::Card;
 use crate::player::Player;
 use crate::resource::Resource;
-use crate::resources::ResourcesBuilder;
+use crate::resources::Resources;
+use crate::rule_set::RuleSet;
+use crate::error::SplendorError;
+use crate::moves::action::Action;
+use crate::moves::all_moves::get_all_moves;
+use crate::moves::legal_actions::get_legal_indices;
+use crate::serialization::GameStateRecord;
+use crate::zobrist;
 
-#[derive(Clone)]
+/// Part of the turn the current player is in
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum TurnPhase {
+    /// The player chooses their main action
+    Action,
+    /// The player holds more tokens than allowed and must return them one by one
+    Discard,
+    /// The player bought a card and may pay gold instead of some of the coloured tokens spent
+    Payment,
+    /// Several aristocrats would visit the player and they must choose one
+    ChooseAristocrat,
+    /// The round in which a player reached the winning points is over,
+    /// or every player passed in a row
+    GameOver,
+}
+
+/// Final standing of a finished game
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameResult {
+    winners: Vec<usize>,
+}
+
+impl GameResult {
+    /// Indices of the players sharing the first place
+    pub fn get_winners(&self) -> &Vec<usize> {
+        &self.winners
+    }
+
+    pub fn is_winner(&self, player_index: usize) -> bool {
+        self.winners.contains(&player_index)
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.winners.len() > 1
+    }
+}
+
+/// Two states are equal when they hold the same position, the order in which cards
+/// and aristocrats were gained aside
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "GameStateRecord", try_from = "GameStateRecord")]
 pub struct GameState {
-    players: Vec<Player>,
-    current_player_index: usize,
-    board: Board,
+    pub(crate) players: Vec<Player>,
+    pub(crate) current_player_index: usize,
+    pub(crate) board: Board,
+    pub(crate) phase: TurnPhase,
+    pub(crate) n_consecutive_passes: u8,
+    pub(crate) payment: Resources,
+    pub(crate) rules: RuleSet,
+    /// Kept up to date by `apply`, which every move goes through, and computed once when a state is set up
+    pub(crate) zobrist_hash: u64,
+}
+
+/// Equal states have equal Zobrist hashes, so hashing the precomputed value is enough
+impl Hash for GameState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist_hash.hash(state);
+    }
 }
 
+/// Positions left to solve. Different move orders often reach the same position, which only needs solving once
+pub type UniquePositions = HashSet<GameState>;
+
 impl GameState {
     pub fn get_board(&self) -> &Board {
         &self.board
@@ -23,52 +89,109 @@ impl GameState {
     pub fn get_current_player_index(&self) -> usize {
         self.current_player_index
     }
+    /// The current player index is checked when the state is built, so it always points to a player
     pub fn get_current_player(&self) -> &Player {
-        &self.players.get(self.current_player_index).unwrap()
+        &self.players[self.current_player_index]
     }
-    
-}
 
-pub fn create_initial_game_state<R: Rng>(n_players: u8, rng: &mut R) -> GameState {
-    let mut players: Vec<Player> = Vec::new();
-    for _ in 0..n_players {
-        players.push(Player::new())
+    pub fn get_player(&self, index: usize) -> Result<&Player, SplendorError> {
+        self.players.get(index).ok_or(SplendorError::InvalidPlayerIndex(index))
     }
-    GameState {
-        players,
-        current_player_index: 0,
-        board: Board::new(n_players as usize, rng),
+
+    pub fn get_rules(&self) -> &RuleSet {
+        &self.rules
     }
-}
 
-pub struct GameStateBuilder {
-    pub players: Vec<crate::player::PlayerBuilder>,
-    pub current_player_index: usize,
-    pub board: crate::board::board::BoardBuilder,
-}
+    /// Zobrist hash of the position, cheap to read as it is maintained incrementally
+    pub fn get_zobrist_hash(&self) -> u64 {
+        self.zobrist_hash
+    }
 
-impl GameStateBuilder {
-    pub fn new(game_state: &GameState) -> Self {
-        Self {
-            players: game_state.players.iter().map(|p| p.to_builder()).collect(),
-            current_player_index: game_state.current_player_index,
-            board: game_state.board.to_builder(),
-        }
+    pub fn get_phase(&self) -> TurnPhase {
+        self.phase
+    }
+
+    /// Whether the current player is about to choose the main action of a turn. Follow-up moves
+    /// of a turn, such as discards, do not count as moves of their own
+    pub fn starts_turn(&self) -> bool {
+        self.phase == TurnPhase::Action
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.phase == TurnPhase::GameOver
+    }
+
+    /// Number of players who passed in a row, the game ends once all of them did
+    pub fn get_n_consecutive_passes(&self) -> u8 {
+        self.n_consecutive_passes
+    }
+
+    /// Coloured tokens spent on the card just bought that gold can still replace
+    pub fn get_payment(&self) -> &Resources {
+        &self.payment
+    }
+
+    /// Board positions of the aristocrats the production of the current player qualifies for
+    pub(crate) fn get_qualifying_aristocrats(&self) -> Vec<usize> {
+        let production = self.get_current_player().get_production();
+        self.board
+            .get_aristocrats()
+            .iter()
+            .enumerate()
+            .filter(|(_, &aristocrat_index)| self.rules.deck().get_aristocrat(aristocrat_index).can_be_taken_with(&production))
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    pub(crate) fn with_zobrist_hash(mut self) -> Self {
+        self.zobrist_hash = zobrist::compute(&self);
+        self
     }
 
-    pub fn get_current_player(&mut self) -> &mut crate::player::PlayerBuilder {
-        &mut self.players[self.current_player_index]
+    /// Moves the current player can make, in move index order
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let all_moves = get_all_moves();
+        get_legal_indices(self).into_iter().map(|index| all_moves[index]).collect()
     }
 
-    pub(crate) fn add_resources_to_player(&mut self, resources_builder: &ResourcesBuilder) {
-        self.players[self.current_player_index].add_resources(resources_builder)
+    /// Bit `i` is set when the move with index `i` is legal.
+    /// The move table has more than 64 entries, so the mask needs 128 bits.
+    pub fn legal_mask(&self) -> u128 {
+        get_legal_indices(self).into_iter().fold(0, |mask, index| mask | 1 << index)
     }
 
-    pub fn build(self) -> GameState {
-        GameState {
-            players: self.players.into_iter().map(|b| b.build()).collect(),
-            current_player_index: self.current_player_index,
-            board: self.board.build(),
+    /// Winners of a finished game: the most points, ties broken by the fewest developed cards.
+    /// Players still equal after that share the win.
+    pub fn get_result(&self) -> Option<GameResult> {
+        if !self.is_game_over() {
+            return None;
         }
+        let standing = |player: &Player| (player.get_points(), -(player.get_deck().len() as i16));
+        let best = self.players.iter().map(standing).max()?;
+        let winners = self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| standing(player) == best)
+            .map(|(index, _)| index)
+            .collect();
+        Some(GameResult { winners })
+    }
+}
+
+pub fn create_initial_game_state<R: Rng>(rules: RuleSet, rng: &mut R) -> GameState {
+    let mut players: Vec<Player> = Vec::new();
+    for _ in 0..rules.n_players() {
+        players.push(Player::new())
+    }
+    GameState {
+        players,
+        current_player_index: 0,
+        board: Board::new(&rules, rng),
+        phase: TurnPhase::Action,
+        n_consecutive_passes: 0,
+        payment: Resources::new(0, 0, 0, 0, 0, 0),
+        rules,
+        zobrist_hash: 0,
     }
+    .with_zobrist_hash()
 }
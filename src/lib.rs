@@ -10,18 +10,33 @@ pub mod board;
 pub mod game_state;
 pub mod moves;
 pub mod state_encoder;
+pub mod rule_set;
+pub mod error;
+pub mod compact_state;
+pub mod observation;
+pub mod serialization;
+pub mod notation;
+pub mod game_record;
+pub mod deck;
+mod zobrist;
+#[cfg(test)]
+mod testing;
 
 use crate::card::card::Card;
-use crate::game_state::create_initial_game_state;
-use crate::moves::all_moves::get_all_moves;
+use crate::game_state::{create_initial_game_state, TurnPhase, UniquePositions};
+use crate::moves::all_moves::{get_all_moves, get_outcomes_after_turns, get_turn_outcomes};
+use crate::moves::action::Action;
 use crate::moves::move_trait::Move;
 use crate::resource::Resource;
-use crate::state_encoder::{OneHotCardEncoder, ParameterEncoder, StateEncoder};
+use crate::rule_set::RuleSet;
+use crate::deck::Deck;
+use crate::error::SplendorError;
+use crate::game_record::GameRecord;
+use crate::state_encoder::{encode_turn, OneHotCardEncoder, ParameterEncoder, StateEncoder};
+use rand::prelude::SliceRandom;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 
-const WINNING_POINTS: u8 = 15;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum EvaluationResult {
     Winning,
@@ -39,95 +54,49 @@ impl EvaluationResult {
     }
 }
 
-fn get_last_player_index(game_state: &game_state::GameState, n_players: u8) -> usize {
-    (game_state.get_current_player_index() + n_players as usize - 1) % n_players as usize
+fn get_player_zero_result(state: &game_state::GameState) -> Option<EvaluationResult> {
+    let result = state.get_result()?;
+    if !result.is_winner(0) {
+        Some(EvaluationResult::Losing)
+    } else if result.is_shared() {
+        Some(EvaluationResult::Draw)
+    } else {
+        Some(EvaluationResult::Winning)
+    }
 }
 
-fn evaluate_player_zero_state(state: &game_state::GameState, n_players: u8, max_depth: u8) -> EvaluationResult {
+fn evaluate_player_zero_state(state: &game_state::GameState, max_depth: u8) -> EvaluationResult {
     if max_depth == 0 {
         return EvaluationResult::Draw;
     }
-    let all_moves = get_all_moves();
     let mut all_children_losing = true;
     let mut has_draw_child = false;
-    let mut player_zero_states: Vec<game_state::GameState> = Vec::new();
-    for valid_move in all_moves.iter().filter(|m| m.is_valid(state)) {
-        let child_state = valid_move.perform(state);
-        let child_last_player_idx = get_last_player_index(&child_state, n_players);
-        let child_points = child_state.get_players()[child_last_player_idx].get_points();
-        let child_n_cards = child_state.get_players()[child_last_player_idx].get_production().sum();
-        if child_points >= WINNING_POINTS {
-            let mut traces: Vec<Vec<game_state::GameState>> = vec![vec![]];
-            for _ in 1..n_players {
-                let mut new_traces = Vec::new();
-                for trace in &traces {
-                    let current = if trace.is_empty() { &child_state } else { trace.last().unwrap() };
-                    for m in all_moves.iter().filter(|m| m.is_valid(current)) {
-                        let mut new_trace = trace.clone();
-                        new_trace.push(m.perform(current));
-                        new_traces.push(new_trace);
-                    }
-                }
-                traces = new_traces;
-            }
-            let is_better = traces.iter().all(|trace| {
-                trace.iter().all(|s| {
-                    let idx = get_last_player_index(s, n_players);
-                    let pts = s.get_players()[idx].get_points();
-                    let cards = s.get_players()[idx].get_production().sum();
-                    child_points > pts || (child_points == pts && child_n_cards < cards)
-                })
-            });
-            if is_better {
-                return EvaluationResult::Winning;
-            }
-            let has_equal = traces.iter().any(|trace| {
-                trace.iter().any(|s| {
-                    let idx = get_last_player_index(s, n_players);
-                    let pts = s.get_players()[idx].get_points();
-                    let cards = s.get_players()[idx].get_production().sum();
-                    child_points == pts && child_n_cards == cards
-                })
-            });
-            if has_equal {
-                has_draw_child = true;
-            }
+    let mut player_zero_states = UniquePositions::new();
+    for child_state in get_turn_outcomes(state) {
+        // Each reply of the other players up to player 0's next turn, cut short when the game ends
+        let round_ends = get_outcomes_after_turns(&child_state, state.get_rules().n_players() - 1);
+        let results: Vec<Option<EvaluationResult>> = round_ends.iter().map(get_player_zero_result).collect();
+        if !results.is_empty() && results.iter().all(|r| *r == Some(EvaluationResult::Winning)) {
+            return EvaluationResult::Winning;
         }
-        let mut has_losing_trace = false;
-        let mut traces: Vec<Vec<game_state::GameState>> = vec![vec![]];
-        for _ in 1..n_players {
-            let mut new_traces = Vec::new();
-            for trace in &traces {
-                let current = if trace.is_empty() { &child_state } else { trace.last().unwrap() };
-                for m in all_moves.iter().filter(|m| m.is_valid(current)) {
-                    let mut new_trace = trace.clone();
-                    new_trace.push(m.perform(current));
-                    new_traces.push(new_trace);
-                }
-            }
-            traces = new_traces;
+        if results.contains(&Some(EvaluationResult::Losing)) {
+            continue;
         }
-        for trace in &traces {
-            if let Some(last) = trace.last() {
-                let idx = get_last_player_index(last, n_players);
-                if last.get_players()[idx].get_points() >= WINNING_POINTS {
-                    has_losing_trace = true;
-                    break;
-                }
-                if last.get_current_player_index() == 0 {
-                    player_zero_states.push(last.clone());
-                }
-            }
+        all_children_losing = false;
+        if results.contains(&Some(EvaluationResult::Draw)) {
+            has_draw_child = true;
         }
-        if !has_losing_trace {
-            all_children_losing = false;
+        for (round_end, result) in round_ends.into_iter().zip(results) {
+            if result.is_none() && round_end.get_current_player_index() == 0 {
+                player_zero_states.insert(round_end);
+            }
         }
     }
     if all_children_losing {
         return EvaluationResult::Losing;
     }
     for s in player_zero_states {
-        let result = evaluate_player_zero_state(&s, n_players, max_depth - 1);
+        let result = evaluate_player_zero_state(&s, max_depth - 1);
         if result == EvaluationResult::Winning {
             return EvaluationResult::Winning;
         }
@@ -138,9 +107,47 @@ fn evaluate_player_zero_state(state: &game_state::GameState, n_players: u8, max_
     if has_draw_child { EvaluationResult::Draw } else { EvaluationResult::Losing }
 }
 
-fn create_encoder(use_one_hot: bool) -> Box<dyn StateEncoder> {
+/// Official rules for the number of players with the given parameters overridden.
+/// A deck given as JSON is registered and played with instead of the official one
+#[allow(clippy::too_many_arguments)]
+fn create_rule_set(
+    n_players: u8,
+    winning_points: Option<u8>,
+    max_tokens: Option<u8>,
+    max_reserve_cards: Option<u8>,
+    n_tokens_per_colour: Option<u8>,
+    n_gold: Option<u8>,
+    n_aristocrats: Option<u8>,
+    deck_json: Option<&str>,
+) -> Result<RuleSet, SplendorError> {
+    let mut rules = RuleSet::official(n_players)?;
+    if let Some(deck_json) = deck_json {
+        rules = rules.with_deck(Deck::from_json(deck_json)?.register());
+    }
+    if let Some(winning_points) = winning_points {
+        rules = rules.with_winning_points(winning_points);
+    }
+    if let Some(n_tokens_per_colour) = n_tokens_per_colour {
+        rules = rules.with_n_tokens_per_colour(n_tokens_per_colour)?;
+    }
+    if let Some(n_gold) = n_gold {
+        rules = rules.with_n_gold(n_gold)?;
+    }
+    if let Some(max_tokens) = max_tokens {
+        rules = rules.with_max_tokens(max_tokens)?;
+    }
+    if let Some(max_reserve_cards) = max_reserve_cards {
+        rules = rules.with_max_reserve_cards(max_reserve_cards)?;
+    }
+    if let Some(n_aristocrats) = n_aristocrats {
+        rules = rules.with_n_aristocrats(n_aristocrats)?;
+    }
+    Ok(rules)
+}
+
+fn create_encoder(use_one_hot: bool, deck: &Deck) -> Box<dyn StateEncoder> {
     if use_one_hot {
-        Box::new(OneHotCardEncoder::new())
+        Box::new(OneHotCardEncoder::new(deck))
     } else {
         Box::new(ParameterEncoder::new())
     }
@@ -148,46 +155,69 @@ fn create_encoder(use_one_hot: bool) -> Box<dyn StateEncoder> {
 
 #[pyclass]
 struct SplendorGame {
-    n_players: u8,
     game_state: Option<game_state::GameState>,
     seed: Option<u64>,
     encoder: Box<dyn StateEncoder>,
+    /// Every move played since the game was created or loaded
+    record: GameRecord,
 }
 
 #[pymethods]
 impl SplendorGame {
     #[new]
-    #[pyo3(signature = (n_players, seed=None, use_one_hot_encoder=true))]
-    fn new(n_players: u8, seed: Option<u64>, use_one_hot_encoder: bool) -> Self {
+    #[pyo3(signature = (n_players, seed=None, use_one_hot_encoder=true, winning_points=None, max_tokens=None, max_reserve_cards=None, n_tokens_per_colour=None, n_gold=None, n_aristocrats=None, deck_json=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        n_players: u8,
+        seed: Option<u64>,
+        use_one_hot_encoder: bool,
+        winning_points: Option<u8>,
+        max_tokens: Option<u8>,
+        max_reserve_cards: Option<u8>,
+        n_tokens_per_colour: Option<u8>,
+        n_gold: Option<u8>,
+        n_aristocrats: Option<u8>,
+        deck_json: Option<&str>,
+    ) -> PyResult<Self> {
+        let rules = create_rule_set(n_players, winning_points, max_tokens, max_reserve_cards, n_tokens_per_colour, n_gold, n_aristocrats, deck_json)?;
         let seed_value = seed.unwrap_or_else(|| {
             rand::thread_rng().gen::<u64>()
         });
-        let mut rng = ChaCha8Rng::seed_from_u64(seed_value);
-        let initial_state = create_initial_game_state(n_players, &mut rng);
-        SplendorGame {
-            n_players,
-            game_state: Some(initial_state),
+        let record = GameRecord::from_seed(rules, seed_value);
+        Ok(SplendorGame {
+            game_state: Some(record.get_final_state().clone()),
             seed: Some(seed_value),
-            encoder: create_encoder(use_one_hot_encoder),
-        }
+            encoder: create_encoder(use_one_hot_encoder, rules.deck()),
+            record,
+        })
     }
 
     fn get_valid_moves(&self) -> PyResult<Vec<usize>> {
         let state = self.game_state.as_ref()
             .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
         
-        let all_moves = get_all_moves();
-        
-        let valid_move_indices: Vec<usize> = all_moves
-            .iter()
-            .enumerate()
-            .filter(|(_, m)| m.is_valid(state))
-            .map(|(i, _)| i)
+        let legal_mask = state.legal_mask();
+        let valid_move_indices: Vec<usize> = (0..get_all_moves().len())
+            .filter(|index| legal_mask >> index & 1 == 1)
             .collect();
             
         Ok(valid_move_indices)
     }
+
+    /// Numpy bool array with one entry per move index, true for the legal moves
+    fn get_legal_mask(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let state = self.game_state.as_ref()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
+        let legal_mask = state.legal_mask();
+        let mask: Vec<bool> = (0..get_all_moves().len())
+            .map(|index| legal_mask >> index & 1 == 1)
+            .collect();
+        let array = py.import("numpy")?.call_method1("array", (mask, "bool"))?;
+        Ok(array.unbind())
+    }
     
+    /// With a seed, a card drawn from the hidden cards is picked at random using it,
+    /// otherwise the top card of the deck shuffled at the start is drawn
     #[pyo3(signature = (move_index, seed=None))]
     fn apply_move(&mut self, move_index: usize, seed: Option<u64>) -> PyResult<SplendorGame> {
         let current_state = self.game_state.as_ref()
@@ -195,30 +225,121 @@ impl SplendorGame {
         
         let all_moves = get_all_moves();
         
-        if move_index >= all_moves.len() {
-            return Err(pyo3::exceptions::PyIndexError::new_err(
-                format!("Move index {} out of range", move_index)
-            ));
-        }
-        
-        let m = &all_moves[move_index];
+        let m = all_moves.get(move_index).ok_or(SplendorError::InvalidMoveIndex(move_index))?;
         
         if !m.is_valid(current_state) {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Invalid move for current game state"
-            ));
+            return Err(SplendorError::InvalidMove.into());
         }
         
-        let new_state = m.perform(current_state);
+        let outcome = seed.and_then(|seed| current_state.chance_outcomes(*m).choose(&mut ChaCha8Rng::seed_from_u64(seed)).copied());
+        let mut record = self.record.clone();
+        record.push(*m, outcome)?;
         let new_seed = seed.or(self.seed);
         Ok(SplendorGame {
-            n_players: self.n_players,
-            game_state: Some(new_state),
+            game_state: Some(record.get_final_state().clone()),
             seed: new_seed,
             encoder: self.encoder.clone_box(),
+            record,
+        })
+    }
+
+    /// Notation of the move with this index, such as `take GBR` or `buy 2.3`
+    #[staticmethod]
+    fn move_to_notation(move_index: usize) -> PyResult<String> {
+        Ok(Action::from_index(move_index)?.to_string())
+    }
+
+    /// Index of the move written in notation
+    #[staticmethod]
+    fn move_from_notation(notation: &str) -> PyResult<usize> {
+        Ok(notation.parse::<Action>()?.to_index()?)
+    }
+
+    /// The game state as versioned JSON, with cards and aristocrats as storage ids
+    fn to_json(&self) -> PyResult<String> {
+        let state = self.game_state.as_ref()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
+        Ok(state.to_json()?)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (json, use_one_hot_encoder=true))]
+    fn from_json(json: &str, use_one_hot_encoder: bool) -> PyResult<SplendorGame> {
+        let game_state = game_state::GameState::from_json(json)?;
+        Ok(SplendorGame {
+            game_state: Some(game_state.clone()),
+            seed: None,
+            encoder: create_encoder(use_one_hot_encoder, game_state.get_rules().deck()),
+            record: GameRecord::new(game_state),
+        })
+    }
+
+    fn to_notation(&self) -> PyResult<String> {
+        let state = self.game_state.as_ref()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
+        Ok(state.to_notation())
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (notation, use_one_hot_encoder=true))]
+    fn from_notation(notation: &str, use_one_hot_encoder: bool) -> PyResult<SplendorGame> {
+        let game_state = game_state::GameState::from_notation(notation)?;
+        Ok(SplendorGame {
+            game_state: Some(game_state.clone()),
+            seed: None,
+            encoder: create_encoder(use_one_hot_encoder, game_state.get_rules().deck()),
+            record: GameRecord::new(game_state),
+        })
+    }
+
+    /// Record of every move played since the game was created or loaded, see `game_record`
+    #[pyo3(signature = (names=None))]
+    fn to_record(&self, names: Option<Vec<String>>) -> PyResult<String> {
+        let record = match names {
+            Some(names) => self.record.clone().with_names(names)?,
+            None => self.record.clone(),
+        };
+        Ok(record.to_text())
+    }
+
+    /// The game at the end of the record, which keeps growing with the moves applied after
+    #[staticmethod]
+    #[pyo3(signature = (record, use_one_hot_encoder=true))]
+    fn from_record(record: &str, use_one_hot_encoder: bool) -> PyResult<SplendorGame> {
+        let record = GameRecord::from_text(record)?;
+        Ok(SplendorGame {
+            game_state: Some(record.get_final_state().clone()),
+            seed: record.get_seed(),
+            encoder: create_encoder(use_one_hot_encoder, record.get_initial_state().get_rules().deck()),
+            record,
         })
     }
 
+    /// The game at its start and after each move of the record
+    #[staticmethod]
+    #[pyo3(signature = (record, use_one_hot_encoder=true))]
+    fn replay_record(record: &str, use_one_hot_encoder: bool) -> PyResult<Vec<SplendorGame>> {
+        let full_record = GameRecord::from_text(record)?;
+        let mut record = GameRecord::new(full_record.get_initial_state().clone()).with_names(full_record.get_names().clone())?;
+        if let Some(seed) = full_record.get_seed() {
+            record = record.with_seed(seed);
+        }
+        let mut games = vec![];
+        let moves = full_record.get_moves();
+        for index in 0..=moves.len() {
+            games.push(SplendorGame {
+                game_state: Some(record.get_final_state().clone()),
+                seed: record.get_seed(),
+                encoder: create_encoder(use_one_hot_encoder, record.get_initial_state().get_rules().deck()),
+                record: record.clone(),
+            });
+            if let Some(&(action, outcome)) = moves.get(index) {
+                record.push(action, outcome)?;
+            }
+        }
+        Ok(games)
+    }
+
     fn get_all_player_points(&self) -> PyResult<Vec<u8>> {
         let state = self.game_state.as_ref()
             .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
@@ -235,40 +356,33 @@ impl SplendorGame {
         let state = self.game_state.as_ref()
             .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
         
-        let max_points = state.get_players()
-            .iter()
-            .map(|p| p.get_points())
-            .max()
-            .unwrap_or(0);
-        
-        Ok(max_points >= 15)
+        Ok(state.is_game_over())
     }
     
     fn get_winner(&self) -> PyResult<Option<usize>> {
         let state = self.game_state.as_ref()
             .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
         
-        let players = state.get_players();
-        let max_points = players
-            .iter()
-            .map(|p| p.get_points())
-            .max()
-            .unwrap_or(0);
-        
-        if max_points >= 15 {
-            // Find player with max points
-            for (index, player) in players.iter().enumerate() {
-                if player.get_points() == max_points {
-                    return Ok(Some(index));
-                }
-            }
-        }
+        // A shared win has no single winner
+        Ok(state.get_result()
+            .filter(|result| !result.is_shared())
+            .map(|result| result.get_winners()[0]))
+    }
+
+    fn get_winners(&self) -> PyResult<Vec<usize>> {
+        let state = self.game_state.as_ref()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
         
-        Ok(None)
+        Ok(state.get_result()
+            .map(|result| result.get_winners().clone())
+            .unwrap_or_default())
     }
 
+    /// Features of the state as the current player sees it. Each player from the current one on
+    /// takes 48 bytes: points, tokens, production and the three reserve slots. The turn phase and
+    /// pending payment follow, then the rows, `48 * n_players + 10 + rows_encoding_size()` bytes in all
     fn get_game_state(&self) -> PyResult<Vec<u8>> {
-        fn add_card_to_state(state: &mut Vec<u8>, card: Option<&&Card>) {
+        fn add_card_to_state(state: &mut Vec<u8>, card: Option<&Card>) {
             if let Some(card) = card {
                 state.push(card.n_points());
                 state.push(card.cost().n_green());
@@ -296,10 +410,12 @@ impl SplendorGame {
         }
         let state = self.game_state.as_ref()
             .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Game state not initialized"))?;
+        // Features only use what the current player can see
+        let observation = state.observe(state.get_current_player_index())?;
         let mut output = Vec::new();
 
-        let players_in_order: Vec<_>  = state.get_players().iter().chain(state.get_players().iter()).collect();
-        for player in &players_in_order[state.get_current_player_index()..state.get_current_player_index()+state.get_players().len()] {
+        let players_in_order: Vec<_>  = observation.get_players().iter().chain(observation.get_players().iter()).collect();
+        for player in &players_in_order[observation.get_current_player_index()..observation.get_current_player_index()+observation.get_players().len()] {
             output.push(player.get_points());
             let resources = player.get_resources();
             output.push(resources.n_green());
@@ -314,18 +430,22 @@ impl SplendorGame {
             output.push(production.n_blue());
             output.push(production.n_black());
             output.push(production.n_white());
+            // The tier tells a card reserved face down by an opponent from an empty slot
             for i in 0..3 {
-                add_card_to_state(&mut output, player.get_reserve().get(i));
+                let reserved = player.get_reserve().get(i);
+                output.push(reserved.map_or(0, |card| card.get_tier().number()));
+                add_card_to_state(&mut output, reserved.and_then(|card| card.get_card()));
             }
         }
-        let rows = state.get_board().get_rows();
-        output.extend(self.encoder.encode_rows(rows));
+        output.extend(encode_turn(&observation));
+        output.extend(self.encoder.encode_rows(&observation));
         Ok(output)
     }
 }
 
 #[pyfunction]
-#[pyo3(signature = (num_games, n_players=2, seed=42, n_moves_limit=69, use_one_hot_encoder=true, max_depth=1))]
+#[pyo3(signature = (num_games, n_players=2, seed=42, n_moves_limit=69, use_one_hot_encoder=true, max_depth=1, winning_points=None, max_tokens=None, max_reserve_cards=None, n_tokens_per_colour=None, n_gold=None, n_aristocrats=None, records_path=None, deck_json=None))]
+#[allow(clippy::too_many_arguments)]
 fn generate_synthetic_data(
     num_games: u32,
     n_players: u8,
@@ -333,41 +453,40 @@ fn generate_synthetic_data(
     n_moves_limit: i32,
     use_one_hot_encoder: bool,
     max_depth: u8,
+    winning_points: Option<u8>,
+    max_tokens: Option<u8>,
+    max_reserve_cards: Option<u8>,
+    n_tokens_per_colour: Option<u8>,
+    n_gold: Option<u8>,
+    n_aristocrats: Option<u8>,
+    records_path: Option<String>,
+    deck_json: Option<&str>,
 ) -> PyResult<(Vec<Vec<u8>>, Vec<i8>, Vec<u8>)> {
     use std::collections::VecDeque;
+    let rules = create_rule_set(n_players, winning_points, max_tokens, max_reserve_cards, n_tokens_per_colour, n_gold, n_aristocrats, deck_json)?;
     let mut rng = ChaCha8Rng::seed_from_u64(seed);
     let history_size = n_players as usize;
-    let encoder = create_encoder(use_one_hot_encoder);
+    let encoder = create_encoder(use_one_hot_encoder, rules.deck());
     let mut all_states: Vec<Vec<u8>> = Vec::new();
     let mut all_labels: Vec<i8> = Vec::new();
     let mut all_n_moves: Vec<u8> = Vec::new();
+    let mut records: Vec<GameRecord> = Vec::new();
     let mut games_generated = 0;
     while games_generated < num_games {
         let mut state_history: VecDeque<game_state::GameState> = VecDeque::with_capacity(history_size);
-        let mut current_state = create_initial_game_state(n_players, &mut rng);
+        let mut record = GameRecord::new(create_initial_game_state(rules, &mut rng))
+            .with_names(vec!["random".to_string(); n_players as usize])?;
         let mut move_num = 0;
         loop {
-            move_num += 1;
-            let all_moves = get_all_moves();
-            let valid_move_indices: Vec<usize> = all_moves
-                .iter()
-                .enumerate()
-                .filter(|(_, m)| m.is_valid(&current_state))
-                .map(|(i, _)| i)
-                .collect();
-            if valid_move_indices.is_empty() {
-                state_history.clear();
-                current_state = create_initial_game_state(n_players, &mut rng);
-                move_num = 0;
-                continue;
+            let current_state = record.get_final_state();
+            if current_state.starts_turn() {
+                move_num += 1;
             }
-            let random_index = valid_move_indices[rng.gen_range(0..valid_move_indices.len())];
-            let chosen_move = &all_moves[random_index];
+            let legal_actions = current_state.legal_actions();
+            let chosen_move = legal_actions[rng.gen_range(0..legal_actions.len())];
             state_history.push_back(current_state.clone());
-            current_state = chosen_move.perform(&current_state);
-            let last_player_index = get_last_player_index(&current_state, n_players);
-            let last_player_points = current_state.get_players()[last_player_index].get_points();
-            if last_player_points >= WINNING_POINTS {
+            record.push(chosen_move, None)?;
+            if record.get_final_state().is_game_over() {
                 break;
             }
         }
@@ -377,17 +496,18 @@ fn generate_synthetic_data(
         let player_zero_state = state_history
             .iter()
             .rev()
-            .find(|state| state.get_current_player_index() == 0)
+            .find(|state| state.get_current_player_index() == 0 && state.get_phase() == TurnPhase::Action)
             .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("Player zero state must exist in history"))?;
-        let evaluation_result = evaluate_player_zero_state(player_zero_state, n_players, max_depth);
+        let evaluation_result = evaluate_player_zero_state(player_zero_state, max_depth);
+        let observation = player_zero_state.observe(player_zero_state.get_current_player_index())?;
         let mut state_bytes = Vec::new();
-        let players_in_order: Vec<_> = player_zero_state
+        let players_in_order: Vec<_> = observation
             .get_players()
             .iter()
-            .chain(player_zero_state.get_players().iter())
+            .chain(observation.get_players().iter())
             .collect();
-        let current_idx = player_zero_state.get_current_player_index();
-        let n_players_usize = player_zero_state.get_players().len();
+        let current_idx = observation.get_current_player_index();
+        let n_players_usize = observation.get_players().len();
         for player in &players_in_order[current_idx..current_idx + n_players_usize] {
             state_bytes.push(player.get_points());
             let resources = player.get_resources();
@@ -404,7 +524,9 @@ fn generate_synthetic_data(
             state_bytes.push(production.n_black());
             state_bytes.push(production.n_white());
             for i in 0..3 {
-                if let Some(card) = player.get_reserve().get(i) {
+                let reserved = player.get_reserve().get(i);
+                state_bytes.push(reserved.map_or(0, |card| card.get_tier().number()));
+                if let Some(card) = reserved.and_then(|card| card.get_card()) {
                     state_bytes.push(card.n_points());
                     state_bytes.push(card.cost().n_green());
                     state_bytes.push(card.cost().n_red());
@@ -423,19 +545,40 @@ fn generate_synthetic_data(
                 }
             }
         }
-        let rows = player_zero_state.get_board().get_rows();
-        state_bytes.extend(encoder.encode_rows(rows));
+        state_bytes.extend(encode_turn(&observation));
+        state_bytes.extend(encoder.encode_rows(&observation));
         all_states.push(state_bytes);
         all_labels.push(evaluation_result.to_label());
         all_n_moves.push(move_num as u8);
+        records.push(record);
         games_generated += 1;
     }
+    // Records of the games kept, to replay them whatever policy generates data later
+    if let Some(records_path) = records_path {
+        std::fs::write(records_path, GameRecord::write_all(&records))?;
+    }
     Ok((all_states, all_labels, all_n_moves))
 }
 
+/// Registers the deck defined in JSON, see `deck`, so that saved states, notations and records
+/// played with it can be loaded. Games set up with `deck_json` register theirs already
+#[pyfunction]
+fn register_deck(deck_json: &str) -> PyResult<()> {
+    Deck::from_json(deck_json)?.register();
+    Ok(())
+}
+
+/// The official deck as JSON, a starting point for custom decks
+#[pyfunction]
+fn get_deck_json() -> String {
+    Deck::official().to_json()
+}
+
 #[pymodule]
 fn splendor(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<SplendorGame>()?;
     m.add_function(wrap_pyfunction!(generate_synthetic_data, m)?)?;
+    m.add_function(wrap_pyfunction!(register_deck, m)?)?;
+    m.add_function(wrap_pyfunction!(get_deck_json, m)?)?;
     Ok(())
 }
\ No newline at end of file
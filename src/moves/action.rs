@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use crate::board::rows::card_reference::CardReference;
+use crate::error::SplendorError;
+use crate::game_state::GameState;
+use crate::moves::all_moves::get_all_moves;
+use crate::moves::build_card::BuildCard;
+use crate::moves::build_from_reserve::BuildFromReserve;
+use crate::moves::choose_aristocrat::ChooseAristocrat;
+use crate::moves::confirm_payment::ConfirmPayment;
+use crate::moves::discard::Discard;
+use crate::moves::get_one::GetOne;
+use crate::moves::get_three::GetThree;
+use crate::moves::get_two::GetTwo;
+use crate::moves::get_two_different::GetTwoDifferent;
+use crate::moves::move_trait::Move;
+use crate::moves::pass::Pass;
+use crate::moves::reserve::Reserve;
+use crate::moves::reserve_from_hidden::ReserveFromHidden;
+use crate::moves::spend_gold::SpendGold;
+use crate::resource::{Resource, GOLD_LETTER};
+use std::fmt;
+use std::str::FromStr;
+
+/// Index in `get_all_moves` of the first move of each kind. Colours follow the order of
+/// `Resource`, in which the table lists them
+const GET_THREE: usize = 0;
+const GET_TWO: usize = 10;
+const RESERVE_OR_BUILD: usize = 15;
+const RESERVE_HIDDEN: usize = 39;
+const BUILD_RESERVED: usize = 42;
+const DISCARD: usize = 45;
+const DISCARD_GOLD: usize = 50;
+const GET_TWO_DIFFERENT: usize = 51;
+const GET_ONE: usize = 61;
+const CHOOSE_ARISTOCRAT: usize = 66;
+const PASS: usize = 71;
+const SPEND_GOLD: usize = 72;
+const CONFIRM_PAYMENT: usize = 77;
+
+/// Position of the pair of colour ranks `low < high` among all pairs in table order
+fn pair_rank(low: usize, high: usize) -> usize {
+    low * (9 - low) / 2 + high - low - 1
+}
+
+/// Every move a player can make, identified by its index in `get_all_moves`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    GetThree(Resource, Resource, Resource),
+    GetTwo(Resource),
+    Reserve { row: u8, position: u8 },
+    Build { row: u8, position: u8 },
+    ReserveHidden(u8),
+    BuildReserved(u8),
+    Discard(Resource),
+    DiscardGold,
+    GetTwoDifferent(Resource, Resource),
+    GetOne(Resource),
+    ChooseAristocrat(u8),
+    Pass,
+    SpendGold(Resource),
+    ConfirmPayment,
+}
+
+impl Action {
+    pub fn from_index(index: usize) -> Result<Self, SplendorError> {
+        get_all_moves().get(index).copied().ok_or(SplendorError::InvalidMoveIndex(index))
+    }
+
+    /// Computed from the fields, the colours of a take in any order.
+    /// Fails when no move of the table has these fields, such as a reserve slot past the last one
+    pub fn to_index(self) -> Result<usize, SplendorError> {
+        let index = match self {
+            Action::GetThree(first, second, third) => {
+                // Each set of three colours leaves out a different pair, listed in reverse order
+                let left_out = 0b11111 & !(1u8 << first as u8 | 1 << second as u8 | 1 << third as u8);
+                GET_THREE + 9 - pair_rank(left_out.trailing_zeros() as usize, 7 - left_out.leading_zeros() as usize)
+            }
+            Action::GetTwo(resource) => GET_TWO + resource as usize,
+            Action::Reserve { row, position } => RESERVE_OR_BUILD + 2 * (4 * row as usize + position as usize),
+            Action::Build { row, position } => RESERVE_OR_BUILD + 2 * (4 * row as usize + position as usize) + 1,
+            Action::ReserveHidden(row) => RESERVE_HIDDEN + row as usize,
+            Action::BuildReserved(index) => BUILD_RESERVED + index as usize,
+            Action::Discard(resource) => DISCARD + resource as usize,
+            Action::DiscardGold => DISCARD_GOLD,
+            Action::GetTwoDifferent(first, second) if first != second => {
+                let (first, second) = (first as usize, second as usize);
+                GET_TWO_DIFFERENT + pair_rank(first.min(second), first.max(second))
+            }
+            Action::GetOne(resource) => GET_ONE + resource as usize,
+            Action::ChooseAristocrat(position) => CHOOSE_ARISTOCRAT + position as usize,
+            Action::Pass => PASS,
+            Action::SpendGold(resource) => SPEND_GOLD + resource as usize,
+            Action::ConfirmPayment => CONFIRM_PAYMENT,
+            Action::GetTwoDifferent(..) => return Err(SplendorError::InvalidAction(self.to_string())),
+        };
+        match get_all_moves().get(index) {
+            Some(&listed) if listed.is_same_move(self) => Ok(index),
+            _ => Err(SplendorError::InvalidAction(self.to_string())),
+        }
+    }
+
+    /// Colours of the tokens taken by a `take` move
+    fn taken_colours(self) -> Option<Vec<Resource>> {
+        match self {
+            Action::GetThree(first, second, third) => Some(vec![first, second, third]),
+            Action::GetTwoDifferent(first, second) => Some(vec![first, second]),
+            Action::GetOne(resource) => Some(vec![resource]),
+            _ => None,
+        }
+    }
+
+    /// Same move, the order of the colours taken aside
+    fn is_same_move(self, other: Action) -> bool {
+        match (self.taken_colours(), other.taken_colours()) {
+            (Some(colours), Some(other_colours)) => {
+                colours.len() == other_colours.len()
+                    && colours.iter().all(|colour| other_colours.contains(colour))
+                    && other_colours.iter().all(|colour| colours.contains(colour))
+            }
+            _ => self == other,
+        }
+    }
+}
+
+/// Move notation: `take GBR`, `take2 W`, `buy 2.3`, `res 1.0`, `res? 3`, `buyr 1`, `discard Y`,
+/// `aristocrat 0`, `pass`, `gold G` and `pay`. Tiers count from 1, card positions, reserve slots
+/// and aristocrats from 0
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Action::GetThree(..) | Action::GetTwoDifferent(..) | Action::GetOne(_) => {
+                let colours: String = self.taken_colours().unwrap_or_default().into_iter().map(Resource::to_letter).collect();
+                write!(f, "take {}", colours)
+            }
+            Action::GetTwo(resource) => write!(f, "take2 {}", resource.to_letter()),
+            Action::Reserve { row, position } => write!(f, "res {}.{}", row + 1, position),
+            Action::Build { row, position } => write!(f, "buy {}.{}", row + 1, position),
+            Action::ReserveHidden(row) => write!(f, "res? {}", row + 1),
+            Action::BuildReserved(index) => write!(f, "buyr {}", index),
+            Action::Discard(resource) => write!(f, "discard {}", resource.to_letter()),
+            Action::DiscardGold => write!(f, "discard {}", GOLD_LETTER),
+            Action::ChooseAristocrat(position) => write!(f, "aristocrat {}", position),
+            Action::Pass => write!(f, "pass"),
+            Action::SpendGold(resource) => write!(f, "gold {}", resource.to_letter()),
+            Action::ConfirmPayment => write!(f, "pay"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = SplendorError;
+
+    /// Reads the notation written by `Display`, taking the colours of a `take` move in any order
+    fn from_str(notation: &str) -> Result<Self, Self::Err> {
+        let invalid = || SplendorError::InvalidNotation(format!("'{}' is not a move", notation));
+        let (verb, argument) = match notation.trim().split_once(' ') {
+            Some((verb, argument)) => (verb, argument.trim()),
+            None => (notation.trim(), ""),
+        };
+        let colours = || argument.chars().map(Resource::from_letter).collect::<Option<Vec<_>>>().ok_or_else(invalid);
+        let colour = || match colours()?[..] {
+            [resource] => Ok(resource),
+            _ => Err(invalid()),
+        };
+        let tier = |text: &str| text.parse::<u8>().ok().and_then(|tier| tier.checked_sub(1)).ok_or_else(invalid);
+        let index = |text: &str| text.parse::<u8>().map_err(|_| invalid());
+        let card = || {
+            let (row, position) = argument.split_once('.').ok_or_else(invalid)?;
+            Ok::<_, SplendorError>((tier(row)?, index(position)?))
+        };
+        let action = match verb {
+            "take" => match colours()?[..] {
+                [first, second, third] => Action::GetThree(first, second, third),
+                [first, second] => Action::GetTwoDifferent(first, second),
+                [resource] => Action::GetOne(resource),
+                _ => return Err(invalid()),
+            },
+            "take2" => Action::GetTwo(colour()?),
+            "res" => {
+                let (row, position) = card()?;
+                Action::Reserve { row, position }
+            }
+            "buy" => {
+                let (row, position) = card()?;
+                Action::Build { row, position }
+            }
+            "res?" => Action::ReserveHidden(tier(argument)?),
+            "buyr" => Action::BuildReserved(index(argument)?),
+            "discard" if argument.eq_ignore_ascii_case(&GOLD_LETTER.to_string()) => Action::DiscardGold,
+            "discard" => Action::Discard(colour()?),
+            "aristocrat" => Action::ChooseAristocrat(index(argument)?),
+            "pass" if argument.is_empty() => Action::Pass,
+            "gold" => Action::SpendGold(colour()?),
+            "pay" if argument.is_empty() => Action::ConfirmPayment,
+            _ => return Err(invalid()),
+        };
+        get_all_moves().iter().copied().find(|&listed| listed.is_same_move(action)).ok_or_else(invalid)
+    }
+}
+
+impl Move for Action {
+    fn is_valid(&self, game_state: &GameState) -> bool {
+        match *self {
+            Action::GetThree(first, second, third) => GetThree::new(first, second, third).is_ok_and(|m| m.is_valid(game_state)),
+            Action::GetTwo(resource) => GetTwo::new(resource).is_valid(game_state),
+            Action::Reserve { row, position } => Reserve::new(CardReference::new(row, position as usize)).is_valid(game_state),
+            Action::Build { row, position } => BuildCard::new(CardReference::new(row, position as usize)).is_valid(game_state),
+            Action::ReserveHidden(row) => ReserveFromHidden::new(row).is_valid(game_state),
+            Action::BuildReserved(index) => BuildFromReserve::new(index as usize).is_valid(game_state),
+            Action::Discard(resource) => Discard::new(resource).is_valid(game_state),
+            Action::DiscardGold => Discard::gold().is_valid(game_state),
+            Action::GetTwoDifferent(first, second) => GetTwoDifferent::new(first, second).is_ok_and(|m| m.is_valid(game_state)),
+            Action::GetOne(resource) => GetOne::new(resource).is_valid(game_state),
+            Action::ChooseAristocrat(position) => ChooseAristocrat::new(position as usize).is_valid(game_state),
+            Action::Pass => Pass.is_valid(game_state),
+            Action::SpendGold(resource) => SpendGold::new(resource).is_valid(game_state),
+            Action::ConfirmPayment => ConfirmPayment.is_valid(game_state),
+        }
+    }
+
+    /// Plays the action on a copy of the state, keeping its Zobrist hash up to date
+    fn perform(&self, game_state: &GameState) -> Result<GameState, SplendorError> {
+        let mut next_state = game_state.clone();
+        next_state.apply(*self)?;
+        Ok(next_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn every_move_round_trips() {
+        let all_moves = get_all_moves();
+        assert_eq!(all_moves.len(), 78);
+        let mut notations = HashSet::new();
+        for (index, &action) in all_moves.iter().enumerate() {
+            let notation = action.to_string();
+            assert_eq!(notation.parse::<Action>().unwrap(), action, "'{}'", notation);
+            assert_eq!(action.to_index(), Ok(index));
+            assert_eq!(Action::from_index(index).unwrap(), action);
+            assert!(notations.insert(notation));
+        }
+    }
+
+    #[test]
+    fn taken_colours_are_read_in_any_order() {
+        for &action in get_all_moves() {
+            if let Some(colours) = action.taken_colours() {
+                let reversed: String = colours.into_iter().rev().map(Resource::to_letter).collect();
+                assert_eq!(format!("take {}", reversed).parse::<Action>().unwrap(), action);
+            }
+        }
+    }
+
+    #[test]
+    fn actions_outside_the_table_have_no_index() {
+        let outside = [
+            Action::BuildReserved(3),
+            Action::Reserve { row: 3, position: 0 },
+            Action::Build { row: 0, position: 4 },
+            Action::ReserveHidden(3),
+            Action::GetThree(Resource::Green, Resource::Green, Resource::Blue),
+            Action::GetTwoDifferent(Resource::Red, Resource::Red),
+            Action::ChooseAristocrat(7),
+        ];
+        for action in outside {
+            assert!(matches!(action.to_index(), Err(SplendorError::InvalidAction(_))), "{:?} has an index", action);
+        }
+    }
+
+    #[test]
+    fn unknown_moves_are_rejected() {
+        for notation in ["", "fly", "take", "take GG", "take GBRW", "take2 Y", "res 0.1", "res 4.0", "res 1.4", "buy 1", "res? 4", "discard Q", "pass now", "pay G"] {
+            assert!(notation.parse::<Action>().is_err(), "'{}' was accepted", notation);
+        }
+    }
+}
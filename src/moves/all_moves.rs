@@ -1,74 +1,172 @@
 use once_cell::sync::Lazy;
-use crate::moves::get_three::GetThree;
-use crate::moves::get_two::GetTwo;
-use crate::moves::reserve::Reserve;
-use crate::moves::build_card::BuildCard;
-use crate::moves::build_from_reserve::BuildFromReserve;
-use crate::moves::move_trait::Move;
+use crate::moves::action::Action;
+use crate::resources::Resources;
+use std::collections::HashSet;
 use crate::resource::Resource;
-use crate::board::rows::card_reference::CardReference;
-use crate::moves::reserve_from_hidden::ReserveFromHidden;
+use crate::game_state::{GameState, TurnPhase};
 
 pub struct AllMoves {
-    moves: Vec<Box<dyn Move>>,
+    moves: Vec<Action>,
 }
 
 impl AllMoves {
     fn new() -> Self {
-        let mut moves: Vec<Box<dyn Move>> = Vec::new();
+        let mut moves: Vec<Action> = Vec::new();
         
         // Get 3 resources - all unique combinations (except gold)
         // There are C(5,3) = 10 combinations
-        moves.push(Box::new(GetThree::new(Resource::Green, Resource::Blue, Resource::Red)));
-        moves.push(Box::new(GetThree::new(Resource::Green, Resource::Blue, Resource::White)));
-        moves.push(Box::new(GetThree::new(Resource::Green, Resource::Blue, Resource::Black)));
-        moves.push(Box::new(GetThree::new(Resource::Green, Resource::Red, Resource::White)));
-        moves.push(Box::new(GetThree::new(Resource::Green, Resource::Red, Resource::Black)));
-        moves.push(Box::new(GetThree::new(Resource::Green, Resource::White, Resource::Black)));
-        moves.push(Box::new(GetThree::new(Resource::Blue, Resource::Red, Resource::White)));
-        moves.push(Box::new(GetThree::new(Resource::Blue, Resource::Red, Resource::Black)));
-        moves.push(Box::new(GetThree::new(Resource::Blue, Resource::White, Resource::Black)));
-        moves.push(Box::new(GetThree::new(Resource::Red, Resource::White, Resource::Black)));
+        moves.push(Action::GetThree(Resource::Green, Resource::Blue, Resource::Red));
+        moves.push(Action::GetThree(Resource::Green, Resource::Blue, Resource::White));
+        moves.push(Action::GetThree(Resource::Green, Resource::Blue, Resource::Black));
+        moves.push(Action::GetThree(Resource::Green, Resource::Red, Resource::White));
+        moves.push(Action::GetThree(Resource::Green, Resource::Red, Resource::Black));
+        moves.push(Action::GetThree(Resource::Green, Resource::White, Resource::Black));
+        moves.push(Action::GetThree(Resource::Blue, Resource::Red, Resource::White));
+        moves.push(Action::GetThree(Resource::Blue, Resource::Red, Resource::Black));
+        moves.push(Action::GetThree(Resource::Blue, Resource::White, Resource::Black));
+        moves.push(Action::GetThree(Resource::Red, Resource::White, Resource::Black));
         
         // Get 2 resources of each type (except gold)
-        moves.push(Box::new(GetTwo::new(Resource::Green)));
-        moves.push(Box::new(GetTwo::new(Resource::Blue)));
-        moves.push(Box::new(GetTwo::new(Resource::Red)));
-        moves.push(Box::new(GetTwo::new(Resource::White)));
-        moves.push(Box::new(GetTwo::new(Resource::Black)));
+        moves.push(Action::GetTwo(Resource::Green));
+        moves.push(Action::GetTwo(Resource::Blue));
+        moves.push(Action::GetTwo(Resource::Red));
+        moves.push(Action::GetTwo(Resource::White));
+        moves.push(Action::GetTwo(Resource::Black));
         
         // Reserve any card - tier index 0-2, card index 0-3
         for tier_index in 0..3 {
             for card_index in 0..4 {
-                moves.push(Box::new(Reserve::new(CardReference::new(tier_index, card_index))));
-                moves.push(Box::new(BuildCard::new(CardReference::new(tier_index, card_index))));
+                moves.push(Action::Reserve { row: tier_index, position: card_index });
+                moves.push(Action::Build { row: tier_index, position: card_index });
             }
         }
         
         // Reserve top - index 0-2
         for index in 0..3 {
-            moves.push(Box::new(ReserveFromHidden::new(index)));
+            moves.push(Action::ReserveHidden(index));
         }
         // Build from reserve - index 0-3
         for index in 0..3 {
-            moves.push(Box::new(BuildFromReserve::new(index)));
+            moves.push(Action::BuildReserved(index));
         }
+
+        // Discard a single token while above the token limit - every colour, then gold
+        moves.push(Action::Discard(Resource::Green));
+        moves.push(Action::Discard(Resource::Blue));
+        moves.push(Action::Discard(Resource::Red));
+        moves.push(Action::Discard(Resource::White));
+        moves.push(Action::Discard(Resource::Black));
+        moves.push(Action::DiscardGold);
+
+        // Get 2 different resources - only when fewer than 3 colours are left in the bank
+        // There are C(5,2) = 10 combinations
+        moves.push(Action::GetTwoDifferent(Resource::Green, Resource::Blue));
+        moves.push(Action::GetTwoDifferent(Resource::Green, Resource::Red));
+        moves.push(Action::GetTwoDifferent(Resource::Green, Resource::White));
+        moves.push(Action::GetTwoDifferent(Resource::Green, Resource::Black));
+        moves.push(Action::GetTwoDifferent(Resource::Blue, Resource::Red));
+        moves.push(Action::GetTwoDifferent(Resource::Blue, Resource::White));
+        moves.push(Action::GetTwoDifferent(Resource::Blue, Resource::Black));
+        moves.push(Action::GetTwoDifferent(Resource::Red, Resource::White));
+        moves.push(Action::GetTwoDifferent(Resource::Red, Resource::Black));
+        moves.push(Action::GetTwoDifferent(Resource::White, Resource::Black));
+
+        // Get 1 resource - only when a single colour is left in the bank
+        moves.push(Action::GetOne(Resource::Green));
+        moves.push(Action::GetOne(Resource::Blue));
+        moves.push(Action::GetOne(Resource::Red));
+        moves.push(Action::GetOne(Resource::White));
+        moves.push(Action::GetOne(Resource::Black));
+
+        // Choose a visiting aristocrat when several qualify - board position 0-4
+        for position in 0..5 {
+            moves.push(Action::ChooseAristocrat(position));
+        }
+
+        // Pass - only when nothing else is possible
+        moves.push(Action::Pass);
+
+        // Pay gold instead of a coloured token spent on the card just bought, then confirm
+        moves.push(Action::SpendGold(Resource::Green));
+        moves.push(Action::SpendGold(Resource::Blue));
+        moves.push(Action::SpendGold(Resource::Red));
+        moves.push(Action::SpendGold(Resource::White));
+        moves.push(Action::SpendGold(Resource::Black));
+        moves.push(Action::ConfirmPayment);
         Self { moves }
     }
     
-    pub fn get_all(&self) -> &Vec<Box<dyn Move>> {
+    pub fn get_all(&self) -> &Vec<Action> {
         &self.moves
     }
     
 
 }
 
-unsafe impl Sync for AllMoves {}
-unsafe impl Send for AllMoves {}
-
 static ALL_MOVES: Lazy<AllMoves> = Lazy::new(AllMoves::new);
 
-pub fn get_all_moves() -> &'static Vec<Box<dyn Move>> {
+pub fn get_all_moves() -> &'static Vec<Action> {
     ALL_MOVES.get_all()
 }
 
+/// Calls `visit` with every state in which the current player's turn can end, starting from
+/// `game_state`. Follow-up moves of the same turn (such as discards) are expanded, so every
+/// visited state has the next player to move or the game over. Moves are played in place with
+/// `apply` and taken back with `undo`, so `game_state` is left as it was
+pub fn for_each_turn_outcome(game_state: &mut GameState, visit: &mut dyn FnMut(&mut GameState)) {
+    for action in game_state.legal_actions() {
+        let Ok(undo) = game_state.apply(action) else {
+            continue;
+        };
+        visit_follow_ups(game_state, &mut HashSet::new(), visit);
+        game_state.undo(undo);
+    }
+}
+
+fn visit_follow_ups(game_state: &mut GameState, seen: &mut HashSet<FollowUpKey>, visit: &mut dyn FnMut(&mut GameState)) {
+    if matches!(game_state.get_phase(), TurnPhase::Action | TurnPhase::GameOver) {
+        visit(game_state);
+        return;
+    }
+    for follow_up in game_state.legal_actions() {
+        let Ok(undo) = game_state.apply(follow_up) else {
+            continue;
+        };
+        if seen.insert(follow_up_key(game_state)) {
+            visit_follow_ups(game_state, seen, visit);
+        }
+        game_state.undo(undo);
+    }
+}
+
+/// Follow-ups only move tokens between the current player and the bank and hand out
+/// aristocrats, so after the same action, different orders reaching the same bank and
+/// aristocrats reach the same state
+type FollowUpKey = (TurnPhase, usize, Resources, Vec<usize>);
+
+fn follow_up_key(game_state: &GameState) -> FollowUpKey {
+    let board = game_state.get_board();
+    (game_state.get_phase(), game_state.get_current_player_index(), board.get_resources().clone(), board.aristocrats.clone())
+}
+
+/// All states in which the current player's turn can end, see `for_each_turn_outcome`
+pub fn get_turn_outcomes(game_state: &GameState) -> Vec<GameState> {
+    let mut outcomes: Vec<GameState> = Vec::new();
+    for_each_turn_outcome(&mut game_state.clone(), &mut |outcome| outcomes.push(outcome.clone()));
+    outcomes
+}
+
+/// States reached once `n_turns` more turns are played from `game_state`, or fewer when the
+/// game ends first. Only the states reached are copied, the turns in between are played in place
+pub fn get_outcomes_after_turns(game_state: &GameState, n_turns: u8) -> Vec<GameState> {
+    fn visit_turns(game_state: &mut GameState, n_turns: u8, outcomes: &mut Vec<GameState>) {
+        if n_turns == 0 || game_state.is_game_over() {
+            outcomes.push(game_state.clone());
+            return;
+        }
+        for_each_turn_outcome(game_state, &mut |outcome| visit_turns(outcome, n_turns - 1, outcomes));
+    }
+    let mut outcomes: Vec<GameState> = Vec::new();
+    visit_turns(&mut game_state.clone(), n_turns, &mut outcomes);
+    outcomes
+}
@@ -0,0 +1,374 @@
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
+use crate::moves::action::Action;
+use crate::moves::chance::ChanceOutcome;
+use crate::moves::move_trait::Move;
+use crate::resources::Resources;
+use crate::zobrist;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+const NO_RESOURCES: Resources = Resources::new(0, 0, 0, 0, 0, 0);
+
+/// Where the card moved by an action came from
+#[derive(Clone, Copy, Debug)]
+enum CardOrigin {
+    /// A visible slot, refilled from the front of the hidden cards when `refilled`.
+    /// The card went to the reserve when `reserved`, to the deck otherwise
+    Board { row: u8, position: u8, card_index: usize, refilled: bool, reserved: bool },
+    /// The front of the hidden cards of the row
+    Hidden { row: u8, card_index: usize },
+    /// The acting player's reserve, `blind` when it was reserved face down
+    Reserve { index: usize, blind: bool },
+}
+
+/// Everything `GameState::apply` changed, so that `GameState::undo` can restore the state exactly
+#[derive(Clone, Debug)]
+pub struct Undo {
+    current_player_index: usize,
+    phase: TurnPhase,
+    n_consecutive_passes: u8,
+    payment: Resources,
+    bank: Resources,
+    player_resources: Resources,
+    card: Option<CardOrigin>,
+    /// Board position and storage index of the aristocrat that visited the acting player
+    aristocrat: Option<(usize, usize)>,
+    /// Row and position of the hidden card a chance outcome brought to the front
+    chance: Option<(u8, usize)>,
+    zobrist_hash: u64,
+}
+
+impl GameState {
+    /// Plays a legal action in place, returning what is needed to take it back.
+    /// Cards are drawn from the top of the hidden cards
+    pub fn apply(&mut self, action: Action) -> Result<Undo, SplendorError> {
+        self.apply_with_chance(action, None)
+    }
+
+    /// Plays a legal action in place, drawing a hidden card chosen at random by `rng`
+    /// instead of the top one, so that the deck order set up at the start does not matter
+    pub fn apply_with_rng<R: Rng>(&mut self, action: Action, rng: &mut R) -> Result<Undo, SplendorError> {
+        let outcome = self.chance_outcomes(action).choose(rng).copied();
+        self.apply_with_chance(action, outcome)
+    }
+
+    /// Plays a legal action in place, drawing the card of `outcome` when the action draws one.
+    /// Without an outcome the top hidden card is drawn
+    pub fn apply_with_chance(&mut self, action: Action, outcome: Option<ChanceOutcome>) -> Result<Undo, SplendorError> {
+        if !action.is_valid(self) {
+            return Err(SplendorError::InvalidMove);
+        }
+        let zobrist_hash = self.zobrist_hash;
+        let chance = match outcome {
+            Some(outcome) => Some(self.bring_to_top(action, outcome)?),
+            None => None,
+        };
+        let player_index = self.current_player_index;
+        let mut undo = Undo {
+            current_player_index: player_index,
+            phase: self.phase,
+            n_consecutive_passes: self.n_consecutive_passes,
+            payment: self.payment.clone(),
+            bank: self.board.resources.clone(),
+            player_resources: self.players[player_index].resources.clone(),
+            card: None,
+            aristocrat: None,
+            chance,
+            zobrist_hash,
+        };
+        match action {
+            Action::GetThree(first, second, third) => {
+                let resources = Resources::single(first).add(&Resources::single(second)).add(&Resources::single(third));
+                self.take_tokens(&resources);
+            }
+            Action::GetTwo(resource) => self.take_tokens(&Resources::single(resource).add(&Resources::single(resource))),
+            Action::GetTwoDifferent(first, second) => self.take_tokens(&Resources::single(first).add(&Resources::single(second))),
+            Action::GetOne(resource) => self.take_tokens(&Resources::single(resource)),
+            Action::Reserve { row, position } => {
+                self.take_gold();
+                let (card_index, origin) = self.take_from_board(row, position, true)?;
+                self.add_to_reserve(card_index, false);
+                undo.card = Some(origin);
+            }
+            Action::ReserveHidden(row) => {
+                let card_index = self.draw_hidden(row)?.ok_or(SplendorError::EmptyDeck(row))?;
+                self.take_gold();
+                self.add_to_reserve(card_index, true);
+                undo.card = Some(CardOrigin::Hidden { row, card_index });
+            }
+            Action::Build { row, position } => {
+                let (card_index, origin) = self.take_from_board(row, position, false)?;
+                self.develop(card_index);
+                undo.card = Some(origin);
+            }
+            Action::BuildReserved(index) => {
+                let index = index as usize;
+                let (card_index, blind) = self.players[player_index].remove_from_reserve(index);
+                self.zobrist_hash ^= zobrist::reserved_card(player_index, card_index);
+                self.develop(card_index);
+                undo.card = Some(CardOrigin::Reserve { index, blind });
+            }
+            Action::Discard(resource) => self.return_tokens(&Resources::single(resource)),
+            Action::DiscardGold => self.return_tokens(&Resources::single_gold()),
+            Action::SpendGold(resource) => {
+                let colour = Resources::single(resource);
+                let gold = Resources::single_gold();
+                self.set_player_resources(self.players[player_index].resources.subtract(&gold).add(&colour));
+                self.set_bank(self.board.resources.subtract(&colour).add(&gold));
+                self.set_payment(self.payment.subtract(&colour));
+            }
+            Action::ConfirmPayment => self.set_payment(NO_RESOURCES),
+            Action::ChooseAristocrat(position) => {
+                undo.aristocrat = Some(self.visit_aristocrat(position as usize));
+                self.end_turn();
+                debug_assert_eq!(self.zobrist_hash, zobrist::compute(self), "Incremental Zobrist hash drifted");
+                return Ok(undo);
+            }
+            Action::Pass => {
+                self.set_n_consecutive_passes(self.n_consecutive_passes + 1);
+                self.end_turn();
+                debug_assert_eq!(self.zobrist_hash, zobrist::compute(self), "Incremental Zobrist hash drifted");
+                return Ok(undo);
+            }
+        }
+        undo.aristocrat = self.finalize();
+        debug_assert_eq!(self.zobrist_hash, zobrist::compute(self), "Incremental Zobrist hash drifted");
+        Ok(undo)
+    }
+
+    /// Takes back the action `undo` was returned for, which must be the last one applied
+    pub fn undo(&mut self, undo: Undo) {
+        let player_index = undo.current_player_index;
+        if let Some((position, aristocrat_index)) = undo.aristocrat {
+            self.players[player_index].remove_last_aristocrat();
+            self.board.aristocrats.insert(position, aristocrat_index);
+        }
+        match undo.card {
+            Some(CardOrigin::Board { row, position, card_index, refilled, reserved }) => {
+                let player = &mut self.players[player_index];
+                if reserved {
+                    player.remove_from_reserve(player.reserve.len() - 1);
+                } else {
+                    player.remove_last_card(self.rules.deck());
+                }
+                let row = self.board.rows.get_row_mut(row).expect("Undo refers to an existing row");
+                let slot = &mut row.visible[position as usize];
+                if refilled {
+                    let refill = slot.take().expect("Refilled slot holds a card");
+                    row.hidden.insert(0, refill);
+                }
+                *slot = Some(card_index);
+            }
+            Some(CardOrigin::Hidden { row, card_index }) => {
+                let player = &mut self.players[player_index];
+                player.remove_from_reserve(player.reserve.len() - 1);
+                let row = self.board.rows.get_row_mut(row).expect("Undo refers to an existing row");
+                row.hidden.insert(0, card_index);
+            }
+            Some(CardOrigin::Reserve { index, blind }) => {
+                let player = &mut self.players[player_index];
+                let card_index = player.remove_last_card(self.rules.deck()).expect("Built card is on top of the deck");
+                player.insert_into_reserve(index, card_index, blind);
+            }
+            None => {}
+        }
+        self.players[player_index].resources = undo.player_resources;
+        self.board.resources = undo.bank;
+        self.payment = undo.payment;
+        self.n_consecutive_passes = undo.n_consecutive_passes;
+        self.phase = undo.phase;
+        if let Some((row, position)) = undo.chance {
+            let row = self.board.rows.get_row_mut(row).expect("Undo refers to an existing row");
+            row.hidden.swap(0, position);
+        }
+        self.current_player_index = player_index;
+        self.zobrist_hash = undo.zobrist_hash;
+    }
+
+    /// Moves the card of the outcome to the top of the hidden cards the action draws from,
+    /// returning the row and the position it came from
+    fn bring_to_top(&mut self, action: Action, outcome: ChanceOutcome) -> Result<(u8, usize), SplendorError> {
+        let row = outcome.get_row();
+        if self.get_draw_row(action) != Some(row) {
+            return Err(SplendorError::InvalidChanceOutcome);
+        }
+        let hidden = &mut self.board.rows.get_row_mut(row)?.hidden;
+        let position = hidden
+            .iter()
+            .position(|&card_index| card_index == outcome.get_card_index())
+            .ok_or(SplendorError::InvalidChanceOutcome)?;
+        let bottom = hidden.len() - 1;
+        let (top_card, drawn_card) = (hidden[0], hidden[position]);
+        hidden.swap(0, position);
+        self.zobrist_hash ^= zobrist::hidden_card(row as usize, bottom, top_card)
+            ^ zobrist::hidden_card(row as usize, bottom - position, drawn_card)
+            ^ zobrist::hidden_card(row as usize, bottom, drawn_card)
+            ^ zobrist::hidden_card(row as usize, bottom - position, top_card);
+        Ok((row, position))
+    }
+
+    fn set_player_resources(&mut self, resources: Resources) {
+        let player_index = self.current_player_index;
+        let player = &mut self.players[player_index];
+        self.zobrist_hash ^= zobrist::player_tokens(player_index, &player.resources) ^ zobrist::player_tokens(player_index, &resources);
+        player.resources = resources;
+    }
+
+    fn set_bank(&mut self, resources: Resources) {
+        self.zobrist_hash ^= zobrist::bank_tokens(&self.board.resources) ^ zobrist::bank_tokens(&resources);
+        self.board.resources = resources;
+    }
+
+    fn set_payment(&mut self, resources: Resources) {
+        self.zobrist_hash ^= zobrist::payment_tokens(&self.payment) ^ zobrist::payment_tokens(&resources);
+        self.payment = resources;
+    }
+
+    fn set_phase(&mut self, phase: TurnPhase) {
+        self.zobrist_hash ^= zobrist::phase(self.phase) ^ zobrist::phase(phase);
+        self.phase = phase;
+    }
+
+    fn set_n_consecutive_passes(&mut self, n_consecutive_passes: u8) {
+        self.zobrist_hash ^= zobrist::consecutive_passes(self.n_consecutive_passes) ^ zobrist::consecutive_passes(n_consecutive_passes);
+        self.n_consecutive_passes = n_consecutive_passes;
+    }
+
+    fn take_tokens(&mut self, resources: &Resources) {
+        self.set_bank(self.board.resources.subtract(resources));
+        self.set_player_resources(self.players[self.current_player_index].resources.add(resources));
+    }
+
+    fn return_tokens(&mut self, resources: &Resources) {
+        self.set_player_resources(self.players[self.current_player_index].resources.subtract(resources));
+        self.set_bank(self.board.resources.add(resources));
+    }
+
+    fn take_gold(&mut self) {
+        if self.board.resources.n_gold() > 0 {
+            self.take_tokens(&Resources::single_gold());
+        }
+    }
+
+    /// Removes the top hidden card of the row, if any
+    fn draw_hidden(&mut self, row: u8) -> Result<Option<usize>, SplendorError> {
+        let hidden = &mut self.board.rows.get_row_mut(row)?.hidden;
+        if hidden.is_empty() {
+            return Ok(None);
+        }
+        let depth = hidden.len() - 1;
+        let card_index = hidden.remove(0);
+        self.zobrist_hash ^= zobrist::hidden_card(row as usize, depth, card_index);
+        Ok(Some(card_index))
+    }
+
+    fn take_from_board(&mut self, row: u8, position: u8, reserved: bool) -> Result<(usize, CardOrigin), SplendorError> {
+        let missing_card = SplendorError::InvalidCardPosition { row_index: row, position: position as usize };
+        let slot = self.board.rows.get_row_mut(row)?.visible.get_mut(position as usize).ok_or(missing_card.clone())?;
+        let card_index = slot.take().ok_or(missing_card)?;
+        self.zobrist_hash ^= zobrist::visible_card(row as usize, position as usize, card_index);
+        let refill = self.draw_hidden(row)?;
+        if let Some(refill) = refill {
+            self.board.rows.get_row_mut(row)?.visible[position as usize] = Some(refill);
+            self.zobrist_hash ^= zobrist::visible_card(row as usize, position as usize, refill);
+        }
+        Ok((card_index, CardOrigin::Board { row, position, card_index, refilled: refill.is_some(), reserved }))
+    }
+
+    fn add_to_reserve(&mut self, card_index: usize, blind: bool) {
+        let player_index = self.current_player_index;
+        let player = &mut self.players[player_index];
+        player.insert_into_reserve(player.reserve.len(), card_index, blind);
+        self.zobrist_hash ^= zobrist::reserved_card(player_index, card_index);
+    }
+
+    /// Pays for the card, moving the tokens to the bank, and adds it to the deck
+    fn develop(&mut self, card_index: usize) {
+        let player_index = self.current_player_index;
+        let card = self.rules.deck().get_card(card_index);
+        let payment = self.players[player_index].get_payment(card);
+        self.return_tokens(&payment);
+        self.set_payment(Resources::new(payment.n_green(), payment.n_red(), payment.n_blue(), payment.n_white(), payment.n_black(), 0));
+        self.players[player_index].add_card(self.rules.deck(), card_index);
+        self.zobrist_hash ^= zobrist::owned_card(player_index, card_index);
+    }
+
+    fn visit_aristocrat(&mut self, position: usize) -> (usize, usize) {
+        let player_index = self.current_player_index;
+        let aristocrat_index = self.board.aristocrats.remove(position);
+        self.players[player_index].add_aristocrat(aristocrat_index);
+        self.zobrist_hash ^= zobrist::board_aristocrat(aristocrat_index) ^ zobrist::player_aristocrat(player_index, aristocrat_index);
+        (position, aristocrat_index)
+    }
+
+    /// Ends the part of the turn the action played: the player may pay gold instead, must discard
+    /// above the token limit or choose between aristocrats, otherwise the turn passes.
+    /// Returns the aristocrat that visited, if any
+    fn finalize(&mut self) -> Option<(usize, usize)> {
+        self.set_n_consecutive_passes(0);
+        if self.players[self.current_player_index].resources.n_gold() > 0 && self.payment.sum() > 0 {
+            self.set_phase(TurnPhase::Payment);
+            return None;
+        }
+        self.set_payment(NO_RESOURCES);
+        if self.players[self.current_player_index].must_discard(&self.rules) {
+            self.set_phase(TurnPhase::Discard);
+            return None;
+        }
+        let qualifying_aristocrats = self.get_qualifying_aristocrats();
+        let mut visit = None;
+        match qualifying_aristocrats.len() {
+            0 => {}
+            1 => visit = Some(self.visit_aristocrat(qualifying_aristocrats[0])),
+            _ => {
+                self.set_phase(TurnPhase::ChooseAristocrat);
+                return None;
+            }
+        }
+        self.end_turn();
+        visit
+    }
+
+    /// Passes the turn to the next player, ending the game once a round in which
+    /// somebody reached the winning points is complete or once every player passed
+    fn end_turn(&mut self) {
+        let next_player_index = (self.current_player_index + 1) % self.players.len();
+        self.zobrist_hash ^= zobrist::current_player(self.current_player_index) ^ zobrist::current_player(next_player_index);
+        self.current_player_index = next_player_index;
+        let is_round_won = self.current_player_index == 0 && self.players.iter().any(|p| p.get_points() >= self.rules.winning_points());
+        let is_stalemate = self.n_consecutive_passes as usize >= self.players.len();
+        if is_round_won || is_stalemate {
+            self.set_phase(TurnPhase::GameOver);
+        } else {
+            self.set_phase(TurnPhase::Action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::random_games;
+
+    #[test]
+    fn undo_restores_the_state() {
+        for record in random_games(12) {
+            for state in record.states().iter().filter(|state| !state.is_game_over()) {
+                let notation = state.to_notation();
+                for action in state.legal_actions() {
+                    // The top card and the bottom one, which is brought to the top first
+                    for outcome in [None, state.chance_outcomes(action).last().copied()] {
+                        let mut next = state.clone();
+                        let undo = next.apply_with_chance(action, outcome).unwrap();
+                        assert_eq!(next.get_zobrist_hash(), zobrist::compute(&next));
+                        next.undo(undo);
+                        assert!(next == *state);
+                        assert_eq!(next.get_zobrist_hash(), state.get_zobrist_hash());
+                        assert_eq!(next.to_notation(), notation);
+                    }
+                }
+            }
+        }
+    }
+}
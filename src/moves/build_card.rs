@@ -1,6 +1,5 @@
 use crate::board::rows::card_reference::CardReference;
-use crate::game_state::{GameState, GameStateBuilder};
-use crate::moves::move_trait::Move;
+use crate::game_state::{GameState, TurnPhase};
 
 pub(crate) struct BuildCard {
     card_reference: CardReference,
@@ -12,28 +11,11 @@ impl BuildCard {
             card_reference: card_reference,
         }
     }
-}
 
-impl Move for BuildCard {
-    fn is_valid(&self, game_state: &GameState) -> bool {
-        self.card_reference.is_in_board(game_state.get_board()) && game_state.get_current_player().get_resources().add(&game_state.get_current_player().get_production()).can_pay(self.card_reference.get_from_board(game_state.get_board()).cost())
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        let Ok(card) = self.card_reference.get_from_board(game_state.get_board(), game_state.get_rules().deck()) else {
+            return false;
+        };
+        game_state.get_phase() == TurnPhase::Action && game_state.get_current_player().get_resources().add(&game_state.get_current_player().get_production()).can_pay(card.cost())
     }
-
-    fn perform(&self, game_state: &GameState) -> GameState {
-        let mut game_state_builder = GameStateBuilder::new(game_state);
-        let card = game_state_builder.board.rows.get(self.card_reference.get_row_index()).remove(self.card_reference.get_card_index());
-        game_state_builder.board.resources.add(&card.cost().to_resources().to_builder());
-        let player = game_state_builder.get_current_player();
-        player.pay_for_card(&card);
-        player.deck.push(card);
-        let board_aristocrats = game_state.get_board().get_aristocrats();
-        for (index, aristocrat) in board_aristocrats.iter().enumerate() {
-            if aristocrat.can_be_taken_by(game_state.get_current_player()) {
-                player.aristocrats.push(aristocrat);
-                game_state_builder.board.aristocrats.remove(index);
-                break;
-            }
-        }
-        self.finalize(game_state_builder)
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use crate::game_state::GameState;
+use crate::moves::action::Action;
+
+/// Card drawn from the hidden cards of a row when an action refills a slot or reserves
+/// from the deck. Every hidden card of the row is an equally likely outcome
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct ChanceOutcome {
+    row: u8,
+    card_index: usize,
+}
+
+impl ChanceOutcome {
+    pub fn new(row: u8, card_index: usize) -> Self {
+        Self { row, card_index }
+    }
+
+    pub fn get_row(&self) -> u8 {
+        self.row
+    }
+
+    /// Index of the drawn card in the card storage
+    pub fn get_card_index(&self) -> usize {
+        self.card_index
+    }
+}
+
+impl GameState {
+    /// Row whose hidden cards the action draws from, if it draws at all
+    pub fn get_draw_row(&self, action: Action) -> Option<u8> {
+        let row = match action {
+            Action::Reserve { row, .. } | Action::Build { row, .. } | Action::ReserveHidden(row) => row,
+            _ => return None,
+        };
+        let has_hidden = self.board.rows.get_row(row).is_ok_and(|row| !row.hidden.is_empty());
+        has_hidden.then_some(row)
+    }
+
+    /// Cards the action may draw, empty when it draws none
+    pub fn chance_outcomes(&self, action: Action) -> Vec<ChanceOutcome> {
+        let Some(row) = self.get_draw_row(action) else {
+            return Vec::new();
+        };
+        self.board.rows.get_row(row)
+            .map(|board_row| board_row.hidden.iter().map(|&card_index| ChanceOutcome::new(row, card_index)).collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,19 @@
+use crate::game_state::{GameState, TurnPhase};
+
+/// Picks which aristocrat visits the player when several qualify at the end of the turn
+pub(crate) struct ChooseAristocrat {
+    position: usize,
+}
+
+impl ChooseAristocrat {
+    pub(crate) fn new(position: usize) -> Self {
+        Self { position }
+    }
+
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        let aristocrats = game_state.get_board().get_aristocrats();
+        game_state.get_phase() == TurnPhase::ChooseAristocrat
+            && self.position < aristocrats.len()
+            && game_state.get_rules().deck().get_aristocrat(aristocrats[self.position]).can_be_taken_with(&game_state.get_current_player().get_production())
+    }
+}
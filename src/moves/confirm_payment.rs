@@ -0,0 +1,10 @@
+use crate::game_state::{GameState, TurnPhase};
+
+/// Keeps the remaining coloured tokens of the payment and ends the purchase
+pub(crate) struct ConfirmPayment;
+
+impl ConfirmPayment {
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        game_state.get_phase() == TurnPhase::Payment
+    }
+}
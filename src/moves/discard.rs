@@ -0,0 +1,26 @@
+use crate::game_state::{GameState, TurnPhase};
+use crate::resource::Resource;
+use crate::resources::Resources;
+
+/// Returns a single token to the bank while the current player is above the token limit
+pub(crate) struct Discard {
+    resources: Resources,
+}
+
+impl Discard {
+    pub(crate) fn new(resource: Resource) -> Self {
+        Self {
+            resources: Resources::single(resource),
+        }
+    }
+
+    pub(crate) fn gold() -> Self {
+        Self {
+            resources: Resources::single_gold(),
+        }
+    }
+
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        game_state.get_phase() == TurnPhase::Discard && game_state.get_current_player().get_resources().contains(&self.resources)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::card::cost::Cost;
+use crate::game_state::{GameState, TurnPhase};
+use crate::resource::Resource;
+
+/// Takes a single token, only allowed when the bank has a single colour left
+pub(crate) struct GetOne {
+    resources: Cost
+}
+
+impl GetOne {
+    pub fn new(resource: Resource) -> Self {
+        let resources = match resource {
+            Resource::Green => Cost::new(1, 0, 0, 0, 0),
+            Resource::Red => Cost::new(0, 1, 0, 0, 0),
+            Resource::Blue => Cost::new(0, 0, 1, 0, 0),
+            Resource::White => Cost::new(0, 0, 0, 1, 0),
+            Resource::Black => Cost::new(0, 0, 0, 0, 1),
+        };
+        Self { resources }
+    }
+
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        let bank = game_state.get_board().get_resources();
+        game_state.get_phase() == TurnPhase::Action && bank.n_colours() < 2 && bank.contains(&self.resources.to_resources())
+    }
+}
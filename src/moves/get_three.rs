@@ -1,17 +1,16 @@
 use crate::card::cost::Cost;
-use crate::game_state::GameState;
-use crate::moves::_give_player_resources::give_player_resources;
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
 use crate::resource::Resource;
-use crate::moves::move_trait::Move;
 
 pub(crate) struct GetThree{
     resources: Cost
 }
 
 impl GetThree {
-    pub fn new(resource1: Resource, resource2: Resource, resource3: Resource) -> Self {
+    pub fn new(resource1: Resource, resource2: Resource, resource3: Resource) -> Result<Self, SplendorError> {
         if resource1 == resource2 || resource1 == resource3 || resource2 == resource3 {
-            panic!("Resources must be unique.");
+            return Err(SplendorError::InvalidMove);
         }
         let mut green: u8 = 0;
         if Resource::Green == resource1 || Resource::Green == resource2 || Resource::Green == resource3 {
@@ -33,19 +32,12 @@ impl GetThree {
         if Resource::White == resource1 || Resource::White == resource2 || Resource::White == resource3 {
             white = 1;
         }
-        Self {
+        Ok(Self {
             resources: Cost::new(green, red, blue, white, black),
-        }
-    }
-
-}
-
-impl Move for GetThree {
-    fn is_valid(&self, game_state: &GameState) -> bool {
-        game_state.get_board().get_resources().can_pay(&self.resources) && game_state.get_current_player().can_add_resources(&self.resources.to_resources())
+        })
     }
 
-    fn perform(&self, game_state: &GameState) -> GameState {
-        self.finalize(give_player_resources(&self.resources, game_state))
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        game_state.get_phase() == TurnPhase::Action && game_state.get_board().get_resources().contains(&self.resources.to_resources())
     }
 }
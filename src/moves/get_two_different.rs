@@ -0,0 +1,32 @@
+use crate::card::cost::Cost;
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
+use crate::resource::Resource;
+
+/// Takes two different colours, only allowed when the bank cannot supply three
+pub(crate) struct GetTwoDifferent {
+    resources: Cost
+}
+
+impl GetTwoDifferent {
+    pub fn new(resource1: Resource, resource2: Resource) -> Result<Self, SplendorError> {
+        if resource1 == resource2 {
+            return Err(SplendorError::InvalidMove);
+        }
+        let count = |resource: Resource| u8::from(resource1 == resource || resource2 == resource);
+        Ok(Self {
+            resources: Cost::new(
+                count(Resource::Green),
+                count(Resource::Red),
+                count(Resource::Blue),
+                count(Resource::White),
+                count(Resource::Black),
+            ),
+        })
+    }
+
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        let bank = game_state.get_board().get_resources();
+        game_state.get_phase() == TurnPhase::Action && bank.n_colours() < 3 && bank.contains(&self.resources.to_resources())
+    }
+}
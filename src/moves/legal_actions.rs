@@ -0,0 +1,79 @@
+use crate::game_state::{GameState, TurnPhase};
+use crate::moves::action::Action;
+use crate::moves::all_moves::get_all_moves;
+use crate::moves::move_trait::Move;
+use crate::resources::Resources;
+
+/// Indices into `get_all_moves` of the moves legal in `game_state`, in table order.
+/// Everything the checks share is derived once instead of once per move.
+pub(crate) fn get_legal_indices(game_state: &GameState) -> Vec<usize> {
+    let player = game_state.get_current_player();
+    let deck = game_state.get_rules().deck();
+    let bank = game_state.get_board().get_resources();
+    let rows = game_state.get_board().get_rows();
+    let production = player.get_production();
+    let purchasing_power = player.get_resources().add(&production);
+    let can_reserve = player.can_add_reserve(game_state.get_rules());
+    let n_bank_colours = bank.n_colours();
+    let has = |resources: &Resources, resource| resources.get(resource) > 0;
+    let is_legal = |action: Action| match action {
+        Action::GetThree(first, second, third) => has(bank, first) && has(bank, second) && has(bank, third),
+        Action::GetTwo(resource) => bank.get(resource) >= 4,
+        Action::Reserve { row, position } => {
+            can_reserve && rows.get_row(row).is_ok_and(|row| row.has_card(position as usize))
+        }
+        Action::Build { row, position } => rows
+            .get_row(row)
+            .ok()
+            .and_then(|row| row.get_card_index(position as usize))
+            .is_some_and(|card_index| purchasing_power.can_pay(deck.get_card(card_index).cost())),
+        Action::ReserveHidden(row) => can_reserve && rows.get_row(row).is_ok_and(|row| !row.get_hidden().is_empty()),
+        Action::BuildReserved(index) => player
+            .get_reserve()
+            .get(index as usize)
+            .is_some_and(|&card_index| purchasing_power.can_pay(deck.get_card(card_index).cost())),
+        Action::GetTwoDifferent(first, second) => n_bank_colours < 3 && has(bank, first) && has(bank, second),
+        Action::GetOne(resource) => n_bank_colours < 2 && has(bank, resource),
+        _ => false,
+    };
+    let mut indices: Vec<usize> = Vec::new();
+    match game_state.get_phase() {
+        TurnPhase::Action => {
+            indices.extend(get_all_moves().iter().enumerate().filter(|(_, &action)| is_legal(action)).map(|(index, _)| index));
+            if indices.is_empty() {
+                indices.push(Action::Pass.to_index().expect("Passing is in the move table"));
+            }
+        }
+        TurnPhase::Discard => {
+            indices.extend(get_all_moves().iter().enumerate().filter(|(_, &action)| match action {
+                Action::Discard(resource) => has(player.get_resources(), resource),
+                Action::DiscardGold => player.get_resources().n_gold() > 0,
+                _ => false,
+            }).map(|(index, _)| index));
+        }
+        TurnPhase::Payment => {
+            let has_gold = player.get_resources().n_gold() > 0;
+            indices.extend(get_all_moves().iter().enumerate().filter(|(_, &action)| match action {
+                Action::SpendGold(resource) => has_gold && has(game_state.get_payment(), resource),
+                Action::ConfirmPayment => true,
+                _ => false,
+            }).map(|(index, _)| index));
+        }
+        TurnPhase::ChooseAristocrat => {
+            let aristocrats = game_state.get_board().get_aristocrats();
+            indices.extend(get_all_moves().iter().enumerate().filter(|(_, &action)| match action {
+                Action::ChooseAristocrat(position) => aristocrats
+                    .get(position as usize)
+                    .is_some_and(|&aristocrat_index| deck.get_aristocrat(aristocrat_index).can_be_taken_with(&production)),
+                _ => false,
+            }).map(|(index, _)| index));
+        }
+        TurnPhase::GameOver => {}
+    }
+    debug_assert_eq!(
+        indices,
+        get_all_moves().iter().enumerate().filter(|(_, action)| action.is_valid(game_state)).map(|(index, _)| index).collect::<Vec<_>>(),
+        "Legal move generation disagrees with Move::is_valid"
+    );
+    indices
+}
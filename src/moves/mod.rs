@@ -2,8 +2,18 @@ pub mod move_trait;
 pub mod get_three;
 pub mod get_two;
 pub mod reserve;
-mod _give_player_resources;
 pub mod build_card;
 pub mod build_from_reserve;
 pub mod all_moves;
+pub mod action;
+pub(crate) mod legal_actions;
+pub mod apply;
+pub mod chance;
 mod reserve_from_hidden;
+mod discard;
+mod get_two_different;
+mod get_one;
+mod choose_aristocrat;
+mod pass;
+mod spend_gold;
+mod confirm_payment;
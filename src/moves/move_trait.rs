@@ -1,10 +1,8 @@
-use crate::game_state::{GameState, GameStateBuilder};
+use crate::error::SplendorError;
+use crate::game_state::GameState;
 
 pub trait Move {
     fn is_valid(&self, game_state: &GameState) -> bool;
-    fn perform(&self, game_state: &GameState) -> GameState;
-    fn finalize(&self, mut game_state_builder: GameStateBuilder) -> GameState {
-        game_state_builder.current_player_index = (game_state_builder.current_player_index + 1) % game_state_builder.players.len();
-        game_state_builder.build()
-    }
-}
\ No newline at end of file
+    /// Plays the move, which must be valid in `game_state`
+    fn perform(&self, game_state: &GameState) -> Result<GameState, SplendorError>;
+}
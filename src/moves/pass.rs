@@ -0,0 +1,16 @@
+use crate::game_state::{GameState, TurnPhase};
+use crate::moves::action::Action;
+use crate::moves::all_moves::get_all_moves;
+use crate::moves::move_trait::Move;
+
+/// Skips the turn, only allowed when no other move is possible
+pub(crate) struct Pass;
+
+impl Pass {
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        game_state.get_phase() == TurnPhase::Action
+            && get_all_moves()
+                .iter()
+                .all(|&action| action == Action::Pass || !action.is_valid(game_state))
+    }
+}
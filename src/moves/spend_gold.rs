@@ -0,0 +1,22 @@
+use crate::game_state::{GameState, TurnPhase};
+use crate::resource::Resource;
+use crate::resources::Resources;
+
+/// Pays a gold token instead of one coloured token spent on the card just bought
+pub(crate) struct SpendGold {
+    resources: Resources,
+}
+
+impl SpendGold {
+    pub(crate) fn new(resource: Resource) -> Self {
+        Self {
+            resources: Resources::single(resource),
+        }
+    }
+
+    pub(crate) fn is_valid(&self, game_state: &GameState) -> bool {
+        game_state.get_phase() == TurnPhase::Payment
+            && game_state.get_current_player().get_resources().n_gold() > 0
+            && game_state.get_payment().contains(&self.resources)
+    }
+}
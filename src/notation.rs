@@ -0,0 +1,328 @@
+//! Single-line text notation of a position, in the spirit of chess FEN.
+//!
+//! Fields are separated by spaces:
+//! `<bank> <rows> <aristocrats> <players> <to move> <phase> <passes> <payment> <deck> [<rules>]`
+//!
+//! - Tokens are colour letters followed by their count, `G4R4B4W4K4Y5`, zeros left out
+//!   and `-` when there are none. K stands for black and Y for gold.
+//! - Rows go from the first tier to the third, separated by `/`. Each row is its four visible
+//!   slots, then `:` and the hidden cards from the top, as card ids separated by `,`.
+//!   An empty slot is `.` and a row without hidden cards ends with `-`.
+//! - Aristocrats are ids separated by `,`, `-` for none.
+//! - Players are separated by `/`, each as `tokens|deck|reserve|aristocrats`.
+//!   Cards reserved face down carry a `?` after their id.
+//! - The phase is one of `action`, `discard`, `payment`, `aristocrat` and `over`.
+//! - The deck is the hash of the deck the ids refer to, in 16 hex digits. It must be registered,
+//!   see `Deck::register`, for the position to be read.
+//! - The rules are left out when they are the official ones for the number of players, otherwise
+//!   they are `winning points,max tokens,max reserve cards,tokens per colour,gold,aristocrats`.
+//!
+//! For example, a two player game about to start, with the hidden cards cut short:
+//! `G4R4B4W4K4Y5 19,23,15,2:4,13,…/65,42,66,58:49,52,…/79,88,85,80:71,77,… 8,7,2 -|-|-|-/-|-|-|- 0 action 0 - f04dc4c0472d0186`
+
+use crate::board::board::Board;
+use crate::board::rows::row::{Row, CARDS_PER_ROW};
+use crate::board::rows::rows::Rows;
+use crate::deck::Deck;
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
+use crate::player::Player;
+use crate::resource::{Resource, GOLD_LETTER};
+use crate::resources::Resources;
+use crate::rule_set::RuleSet;
+use crate::serialization::{check_aristocrat_id, check_card_id, check_state, find_deck};
+
+const NONE: &str = "-";
+const EMPTY_SLOT: &str = ".";
+const BLIND_MARK: char = '?';
+
+fn notation_error(reason: impl Into<String>) -> SplendorError {
+    SplendorError::InvalidNotation(reason.into())
+}
+
+fn format_tokens(resources: &Resources) -> String {
+    let counts = Resource::ALL
+        .iter()
+        .map(|&resource| (resource.to_letter(), resources.get(resource)))
+        .chain([(GOLD_LETTER, resources.n_gold())]);
+    let text: String = counts.filter(|&(_, count)| count > 0).map(|(letter, count)| format!("{}{}", letter, count)).collect();
+    if text.is_empty() { NONE.to_string() } else { text }
+}
+
+fn parse_tokens(text: &str) -> Result<Resources, SplendorError> {
+    let mut counts = [None::<u8>; 6];
+    if text != NONE {
+        let mut chars = text.chars().peekable();
+        while let Some(letter) = chars.next() {
+            let slot = match Resource::from_letter(letter) {
+                Some(resource) => Resource::ALL.iter().position(|&r| r == resource).expect("Every colour is listed"),
+                None if letter.to_ascii_uppercase() == GOLD_LETTER => 5,
+                None => return Err(notation_error(format!("unknown token letter '{}' in '{}'", letter, text))),
+            };
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            let count = digits.parse().map_err(|_| notation_error(format!("missing count after '{}' in '{}'", letter, text)))?;
+            if counts[slot].replace(count).is_some() {
+                return Err(notation_error(format!("'{}' appears twice in '{}'", letter, text)));
+            }
+        }
+    }
+    let [green, red, blue, white, black, gold] = counts.map(|count| count.unwrap_or(0));
+    Ok(Resources::new(green, red, blue, white, black, gold))
+}
+
+fn format_ids(ids: impl IntoIterator<Item = String>) -> String {
+    let ids: Vec<String> = ids.into_iter().collect();
+    if ids.is_empty() { NONE.to_string() } else { ids.join(",") }
+}
+
+fn parse_id(text: &str) -> Result<usize, SplendorError> {
+    text.parse().map_err(|_| notation_error(format!("'{}' is not an id", text)))
+}
+
+fn parse_ids(text: &str) -> Result<Vec<usize>, SplendorError> {
+    if text == NONE {
+        return Ok(Vec::new());
+    }
+    text.split(',').map(parse_id).collect()
+}
+
+fn format_row(row: &Row) -> String {
+    let visible: Vec<String> = row.visible.iter().map(|slot| slot.map_or(EMPTY_SLOT.to_string(), |id| id.to_string())).collect();
+    format!("{}:{}", visible.join(","), format_ids(row.hidden.iter().map(|id| id.to_string())))
+}
+
+fn parse_row(text: &str) -> Result<Row, SplendorError> {
+    let (visible, hidden) = text.split_once(':').ok_or_else(|| notation_error(format!("row '{}' has no ':'", text)))?;
+    let visible = visible
+        .split(',')
+        .map(|slot| if slot == EMPTY_SLOT { Ok(None) } else { parse_id(slot).map(Some) })
+        .collect::<Result<Vec<_>, _>>()?;
+    if visible.len() != CARDS_PER_ROW {
+        return Err(notation_error(format!("row '{}' needs {} visible slots", text, CARDS_PER_ROW)));
+    }
+    Ok(Row { visible, hidden: parse_ids(hidden)? })
+}
+
+fn format_player(player: &Player) -> String {
+    let reserve = player.reserve
+        .iter()
+        .enumerate()
+        .map(|(index, id)| if player.is_reserved_blind(index) { format!("{}{}", id, BLIND_MARK) } else { id.to_string() });
+    [
+        format_tokens(&player.resources),
+        format_ids(player.deck.iter().map(|id| id.to_string())),
+        format_ids(reserve),
+        format_ids(player.aristocrats.iter().map(|id| id.to_string())),
+    ]
+    .join("|")
+}
+
+fn parse_player(text: &str, deck: &Deck) -> Result<Player, SplendorError> {
+    let [tokens, owned, reserve, aristocrats]: [&str; 4] = text
+        .split('|')
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| notation_error(format!("player '{}' needs tokens, deck, reserve and aristocrats", text)))?;
+    let mut player = Player::new();
+    player.resources = parse_tokens(tokens)?;
+    for card_index in parse_ids(owned)? {
+        player.add_card(deck, check_card_id(deck, card_index, SplendorError::InvalidNotation)?);
+    }
+    if reserve != NONE {
+        for (index, id) in reserve.split(',').enumerate() {
+            let (id, blind) = match id.strip_suffix(BLIND_MARK) {
+                Some(id) => (id, true),
+                None => (id, false),
+            };
+            let card_index = check_card_id(deck, parse_id(id)?, SplendorError::InvalidNotation)?;
+            player.insert_into_reserve(index, card_index, blind);
+        }
+    }
+    for aristocrat_index in parse_ids(aristocrats)? {
+        player.add_aristocrat(check_aristocrat_id(deck, aristocrat_index, SplendorError::InvalidNotation)?);
+    }
+    Ok(player)
+}
+
+fn phase_name(phase: TurnPhase) -> &'static str {
+    match phase {
+        TurnPhase::Action => "action",
+        TurnPhase::Discard => "discard",
+        TurnPhase::Payment => "payment",
+        TurnPhase::ChooseAristocrat => "aristocrat",
+        TurnPhase::GameOver => "over",
+    }
+}
+
+fn parse_phase(text: &str) -> Result<TurnPhase, SplendorError> {
+    [TurnPhase::Action, TurnPhase::Discard, TurnPhase::Payment, TurnPhase::ChooseAristocrat, TurnPhase::GameOver]
+        .into_iter()
+        .find(|&phase| phase_name(phase) == text)
+        .ok_or_else(|| notation_error(format!("unknown phase '{}'", text)))
+}
+
+pub(crate) fn format_rules(rules: &RuleSet) -> String {
+    [rules.winning_points(), rules.max_tokens(), rules.max_reserve_cards(), rules.n_tokens_per_colour(), rules.n_gold(), rules.n_aristocrats()]
+        .map(|value| value.to_string())
+        .join(",")
+}
+
+pub(crate) fn parse_rules(text: &str, n_players: u8) -> Result<RuleSet, SplendorError> {
+    let values = text
+        .split(',')
+        .map(|value| value.parse::<u8>().map_err(|_| notation_error(format!("'{}' is not a rule value", value))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [winning_points, max_tokens, max_reserve_cards, n_tokens_per_colour, n_gold, n_aristocrats]: [u8; 6] = values
+        .try_into()
+        .map_err(|_| notation_error(format!("rules '{}' need six values", text)))?;
+    RuleSet::official(n_players)?
+        .with_winning_points(winning_points)
+        .with_n_tokens_per_colour(n_tokens_per_colour)?
+        .with_n_gold(n_gold)?
+        .with_max_tokens(max_tokens)?
+        .with_max_reserve_cards(max_reserve_cards)?
+        .with_n_aristocrats(n_aristocrats)
+}
+
+impl GameState {
+    /// The position on one line, see the `notation` module for the format
+    pub fn to_notation(&self) -> String {
+        let rows: Vec<String> = (0..3)
+            .map(|index| format_row(self.board.rows.get_row(index).expect("Rows hold every tier")))
+            .collect();
+        let players: Vec<String> = self.players.iter().map(format_player).collect();
+        let mut fields = vec![
+            format_tokens(&self.board.resources),
+            rows.join("/"),
+            format_ids(self.board.aristocrats.iter().map(|id| id.to_string())),
+            players.join("/"),
+            self.current_player_index.to_string(),
+            phase_name(self.phase).to_string(),
+            self.n_consecutive_passes.to_string(),
+            format_tokens(&self.payment),
+            format!("{:016x}", self.rules.deck().get_hash()),
+        ];
+        if RuleSet::official(self.rules.n_players()).ok().map(|rules| rules.with_deck(self.rules.deck())) != Some(self.rules) {
+            fields.push(format_rules(&self.rules));
+        }
+        fields.join(" ")
+    }
+
+    pub fn from_notation(notation: &str) -> Result<Self, SplendorError> {
+        let fields: Vec<&str> = notation.split_whitespace().collect();
+        let [bank, rows, aristocrats, players, current_player_index, phase, n_consecutive_passes, payment, deck] = fields[..fields.len().min(9)] else {
+            return Err(notation_error(format!("expected 9 or 10 fields, got {}", fields.len())));
+        };
+        if fields.len() > 10 {
+            return Err(notation_error(format!("expected 9 or 10 fields, got {}", fields.len())));
+        }
+        let deck = u64::from_str_radix(deck, 16).map_err(|_| notation_error(format!("'{}' is not a deck hash", deck)))?;
+        let deck = find_deck(deck, SplendorError::InvalidNotation)?;
+        let [first, second, third]: [Row; 3] = rows
+            .split('/')
+            .map(parse_row)
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .map_err(|_| notation_error("expected three rows"))?;
+        let players = players.split('/').map(|player| parse_player(player, deck)).collect::<Result<Vec<_>, _>>()?;
+        let n_players = u8::try_from(players.len()).map_err(|_| SplendorError::InvalidPlayerCount(u8::MAX))?;
+        let rules = match fields.get(9) {
+            Some(rules) => parse_rules(rules, n_players)?,
+            None => RuleSet::official(n_players)?,
+        }
+        .with_deck(deck);
+        let game_state = GameState {
+            players,
+            current_player_index: current_player_index.parse().map_err(|_| notation_error(format!("'{}' is not a player index", current_player_index)))?,
+            board: Board {
+                resources: parse_tokens(bank)?,
+                rows: Rows::from_rows([first, second, third]),
+                aristocrats: parse_ids(aristocrats)?,
+            },
+            phase: parse_phase(phase)?,
+            n_consecutive_passes: n_consecutive_passes.parse().map_err(|_| notation_error(format!("'{}' is not a number of passes", n_consecutive_passes)))?,
+            payment: parse_tokens(payment)?,
+            rules,
+            zobrist_hash: 0,
+        };
+        check_state(&game_state, SplendorError::InvalidNotation)?;
+        Ok(game_state.with_zobrist_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::create_initial_game_state;
+    use crate::testing::{custom_deck, random_games};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn assert_round_trips(state: &GameState) {
+        let notation = state.to_notation();
+        let parsed = GameState::from_notation(&notation).unwrap();
+        assert!(parsed == *state);
+        assert_eq!(parsed.get_zobrist_hash(), state.get_zobrist_hash());
+        assert_eq!(parsed.to_notation(), notation);
+    }
+
+    #[test]
+    fn positions_round_trip() {
+        for state in random_games(6).flat_map(|record| record.states()) {
+            assert_round_trips(&state);
+        }
+    }
+
+    #[test]
+    fn official_rules_are_left_out() {
+        let state = create_initial_game_state(RuleSet::official(2).unwrap(), &mut ChaCha8Rng::seed_from_u64(0));
+        assert_eq!(state.to_notation().split_whitespace().count(), 9);
+        assert!(state.to_notation().ends_with(&format!(" 0 action 0 - {:016x}", Deck::official().get_hash())));
+        let rules = RuleSet::official(3).unwrap().with_winning_points(12).with_n_aristocrats(2).unwrap();
+        let state = create_initial_game_state(rules, &mut ChaCha8Rng::seed_from_u64(0));
+        assert_eq!(state.to_notation().split_whitespace().count(), 10);
+        assert_round_trips(&state);
+    }
+
+    #[test]
+    fn positions_carry_their_deck() {
+        let rules = RuleSet::official(2).unwrap().with_deck(custom_deck());
+        let state = create_initial_game_state(rules, &mut ChaCha8Rng::seed_from_u64(0));
+        let notation = state.to_notation();
+        assert_eq!(notation.split_whitespace().count(), 9);
+        assert!(notation.ends_with(&format!("{:016x}", custom_deck().get_hash())));
+        assert_round_trips(&state);
+    }
+
+    #[test]
+    fn malformed_notation_is_rejected() {
+        let state = create_initial_game_state(RuleSet::official(2).unwrap(), &mut ChaCha8Rng::seed_from_u64(0));
+        let notation = state.to_notation();
+        let fields: Vec<&str> = notation.split_whitespace().collect();
+        let with_field = |index: usize, value: &str| {
+            let mut fields = fields.clone();
+            fields[index] = value;
+            fields.join(" ")
+        };
+        let first_row = fields[1].split('/').next().unwrap();
+        let (visible, hidden) = first_row.split_once(':').unwrap();
+        let three_slots = format!("{}:{}", visible.rsplit_once(',').unwrap().0, hidden);
+        let malformed = [
+            fields[..8].join(" "),
+            with_field(0, "G4R4Q4"),
+            with_field(0, "G4G4"),
+            with_field(1, &fields[1].replacen(first_row, &three_slots, 1)),
+            with_field(2, "999"),
+            with_field(4, "2"),
+            with_field(5, "bid"),
+            with_field(8, "official"),
+            with_field(8, &format!("{:016x}", Deck::official().get_hash() ^ 1)),
+        ];
+        for text in malformed {
+            assert!(GameState::from_notation(&text).is_err(), "'{}' was accepted", text);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use crate::board::board::Board;
+use crate::board::rows::row::Row;
+use crate::board::rows::rows::Rows;
+use crate::card::card::Card;
+use crate::card::tier::Tier;
+use crate::deck::Deck;
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
+use crate::player::Player;
+use crate::resources::Resources;
+use crate::rule_set::RuleSet;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// A reserved card as a given player sees it
+#[derive(Clone, Copy)]
+pub enum ReservedCard {
+    /// Reserved from the board, or held by the observing player
+    Known { card_index: usize, card: &'static Card },
+    /// Reserved face down by an opponent, only its tier is public
+    Unknown(Tier),
+}
+
+impl ReservedCard {
+    pub fn get_card(&self) -> Option<&'static Card> {
+        match self {
+            ReservedCard::Known { card, .. } => Some(card),
+            ReservedCard::Unknown(_) => None,
+        }
+    }
+
+    /// Storage id of the card, when known
+    pub fn get_card_index(&self) -> Option<usize> {
+        match self {
+            ReservedCard::Known { card_index, .. } => Some(*card_index),
+            ReservedCard::Unknown(_) => None,
+        }
+    }
+
+    pub fn get_tier(&self) -> Tier {
+        match self {
+            ReservedCard::Known { card, .. } => card.tier(),
+            ReservedCard::Unknown(tier) => *tier,
+        }
+    }
+}
+
+/// Public view of a player, with face down reserved cards hidden unless the player is the observer
+#[derive(Clone)]
+pub struct PlayerObservation {
+    resources: Resources,
+    production: Resources,
+    points: u8,
+    deck: Vec<usize>,
+    reserve: Vec<ReservedCard>,
+    reserved_blind: Vec<bool>,
+    aristocrats: Vec<usize>,
+}
+
+impl PlayerObservation {
+    fn new(player: &Player, is_observer: bool, deck: &'static Deck) -> Self {
+        let reserve = player.reserve
+            .iter()
+            .enumerate()
+            .map(|(index, &card_index)| {
+                let card = deck.get_card(card_index);
+                if is_observer || !player.is_reserved_blind(index) {
+                    ReservedCard::Known { card_index, card }
+                } else {
+                    ReservedCard::Unknown(card.tier())
+                }
+            })
+            .collect();
+        Self {
+            resources: player.resources.clone(),
+            production: player.get_production(),
+            points: player.get_points(),
+            deck: player.deck.clone(),
+            reserve,
+            reserved_blind: player.reserved_blind.clone(),
+            aristocrats: player.aristocrats.clone(),
+        }
+    }
+
+    /// The player with the unknown reserved cards drawn from `unseen`, one list of card indices per tier
+    fn to_player(&self, unseen: &mut [Vec<usize>; 3], deck: &Deck) -> Player {
+        let mut player = Player::new();
+        player.resources = self.resources.clone();
+        for &card_index in &self.deck {
+            player.add_card(deck, card_index);
+        }
+        for &aristocrat in &self.aristocrats {
+            player.add_aristocrat(aristocrat);
+        }
+        for (index, reserved_card) in self.reserve.iter().enumerate() {
+            let card_index = match reserved_card {
+                ReservedCard::Known { card_index, .. } => *card_index,
+                ReservedCard::Unknown(tier) => unseen[tier.index()].pop().expect("Unseen cards include the face down reserves"),
+            };
+            player.insert_into_reserve(index, card_index, self.reserved_blind[index]);
+        }
+        player
+    }
+
+    pub fn get_resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn get_production(&self) -> &Resources {
+        &self.production
+    }
+
+    pub fn get_points(&self) -> u8 {
+        self.points
+    }
+
+    /// Storage ids of the developed cards
+    pub fn get_deck(&self) -> &Vec<usize> {
+        &self.deck
+    }
+
+    pub fn get_reserve(&self) -> &Vec<ReservedCard> {
+        &self.reserve
+    }
+
+    /// Whether the reserved card at the index was taken face down, which everybody can see
+    pub fn is_reserved_blind(&self, index: usize) -> bool {
+        self.reserved_blind.get(index).copied().unwrap_or(false)
+    }
+
+    /// Storage ids of the aristocrats who visited the player
+    pub fn get_aristocrats(&self) -> &Vec<usize> {
+        &self.aristocrats
+    }
+}
+
+/// Visible cards of a row and the number of hidden cards left, but not their order
+#[derive(Clone)]
+pub struct RowObservation {
+    visible: Vec<Option<usize>>,
+    n_hidden: usize,
+    deck: &'static Deck,
+}
+
+impl RowObservation {
+    /// Storage id of the visible card at the position
+    pub fn get_card_index(&self, position: usize) -> Option<usize> {
+        self.visible.get(position).copied().flatten()
+    }
+
+    pub fn get_card(&self, position: usize) -> Option<&'static Card> {
+        self.get_card_index(position).map(|card_index| self.deck.get_card(card_index))
+    }
+
+    pub fn get_n_hidden(&self) -> usize {
+        self.n_hidden
+    }
+}
+
+/// Everything a player is allowed to know about the game
+#[derive(Clone)]
+pub struct Observation {
+    observer_index: usize,
+    players: Vec<PlayerObservation>,
+    current_player_index: usize,
+    rows: Vec<RowObservation>,
+    bank: Resources,
+    aristocrats: Vec<usize>,
+    phase: TurnPhase,
+    n_consecutive_passes: u8,
+    payment: Resources,
+    rules: RuleSet,
+}
+
+impl Observation {
+    pub fn get_observer_index(&self) -> usize {
+        self.observer_index
+    }
+
+    pub fn get_players(&self) -> &Vec<PlayerObservation> {
+        &self.players
+    }
+
+    pub fn get_player(&self, index: usize) -> Result<&PlayerObservation, SplendorError> {
+        self.players.get(index).ok_or(SplendorError::InvalidPlayerIndex(index))
+    }
+
+    pub fn get_observer(&self) -> &PlayerObservation {
+        &self.players[self.observer_index]
+    }
+
+    pub fn get_current_player_index(&self) -> usize {
+        self.current_player_index
+    }
+
+    pub fn get_row(&self, index: u8) -> Result<&RowObservation, SplendorError> {
+        self.rows.get(index as usize).ok_or(SplendorError::InvalidRowIndex(index))
+    }
+
+    pub fn get_bank(&self) -> &Resources {
+        &self.bank
+    }
+
+    /// Storage ids of the aristocrats on the board
+    pub fn get_aristocrats(&self) -> &Vec<usize> {
+        &self.aristocrats
+    }
+
+    pub fn get_phase(&self) -> TurnPhase {
+        self.phase
+    }
+
+    pub fn get_n_consecutive_passes(&self) -> u8 {
+        self.n_consecutive_passes
+    }
+
+    pub fn get_payment(&self) -> &Resources {
+        &self.payment
+    }
+
+    pub fn get_rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Cards of each tier the observer has not seen: neither on the board, nor owned,
+    /// nor reserved face up or by the observer
+    fn get_unseen_cards(&self) -> [Vec<usize>; 3] {
+        let mut seen = HashSet::new();
+        for row in &self.rows {
+            seen.extend(row.visible.iter().flatten().copied());
+        }
+        for player in &self.players {
+            seen.extend(player.deck.iter().copied());
+            seen.extend(player.reserve.iter().filter_map(|card| card.get_card_index()));
+        }
+        Tier::ALL.map(|tier| self.rules.deck().get_card_storage().get_tier_indices(tier).into_iter().filter(|index| !seen.contains(index)).collect())
+    }
+
+    /// A full state consistent with the observation, for determinized search. The unseen cards
+    /// of each tier are shuffled, handed out as the opponents' face down reserves,
+    /// and the rest become the hidden cards of the row
+    pub fn sample_state<R: Rng>(&self, rng: &mut R) -> GameState {
+        let mut unseen = self.get_unseen_cards();
+        for cards in unseen.iter_mut() {
+            cards.shuffle(rng);
+        }
+        let players = self.players.iter().map(|player| player.to_player(&mut unseen, self.rules.deck())).collect();
+        let rows: [Row; 3] = std::array::from_fn(|index| {
+            let hidden = std::mem::take(&mut unseen[index]);
+            debug_assert_eq!(hidden.len(), self.rows[index].n_hidden, "Unseen cards fill the hidden cards exactly");
+            Row {
+                visible: self.rows[index].visible.clone(),
+                hidden,
+            }
+        });
+        GameState {
+            players,
+            current_player_index: self.current_player_index,
+            board: Board {
+                resources: self.bank.clone(),
+                rows: Rows::from_rows(rows),
+                aristocrats: self.aristocrats.clone(),
+            },
+            phase: self.phase,
+            n_consecutive_passes: self.n_consecutive_passes,
+            payment: self.payment.clone(),
+            rules: self.rules,
+            zobrist_hash: 0,
+        }
+        .with_zobrist_hash()
+    }
+}
+
+impl GameState {
+    /// What the player can see: hidden cards are only counted and the cards opponents
+    /// reserved face down only show their tier
+    pub fn observe(&self, player_index: usize) -> Result<Observation, SplendorError> {
+        self.get_player(player_index)?;
+        let mut rows = Vec::with_capacity(3);
+        for index in 0..3 {
+            let row = self.board.rows.get_row(index)?;
+            rows.push(RowObservation {
+                visible: row.visible.clone(),
+                n_hidden: row.hidden.len(),
+                deck: self.rules.deck(),
+            });
+        }
+        Ok(Observation {
+            observer_index: player_index,
+            players: self.players.iter().enumerate().map(|(index, player)| PlayerObservation::new(player, index == player_index, self.rules.deck())).collect(),
+            current_player_index: self.current_player_index,
+            rows,
+            bank: self.board.resources.clone(),
+            aristocrats: self.board.aristocrats.clone(),
+            phase: self.phase,
+            n_consecutive_passes: self.n_consecutive_passes,
+            payment: self.payment.clone(),
+            rules: self.rules,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::random_games;
+    use crate::zobrist;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// Runs `check` on every state of a few random games, for every observer
+    fn for_each_observation(check: impl Fn(&GameState, &Observation, &mut ChaCha8Rng)) {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for record in random_games(12) {
+            for state in record.states().iter().filter(|state| !state.is_game_over()) {
+                for observer_index in 0..state.get_players().len() {
+                    let observation = state.observe(observer_index).unwrap();
+                    check(state, &observation, &mut rng);
+                }
+            }
+        }
+    }
+
+    /// How many times each card of the storage appears anywhere in the state
+    fn count_cards(state: &GameState) -> Vec<usize> {
+        let mut counts = vec![0; state.get_rules().deck().n_cards()];
+        for player in state.get_players() {
+            for &card_index in player.get_deck().iter().chain(player.get_reserve()) {
+                counts[card_index] += 1;
+            }
+        }
+        for row_index in 0..3 {
+            let row = state.get_board().get_rows().get_row(row_index).unwrap();
+            for &card_index in row.visible.iter().flatten().chain(&row.hidden) {
+                counts[card_index] += 1;
+            }
+        }
+        counts
+    }
+
+    fn count_tokens(state: &GameState) -> Resources {
+        state.get_players().iter().fold(state.get_board().get_resources().clone(), |total, player| total.add(player.get_resources()))
+    }
+
+    #[test]
+    fn sampled_states_conserve_cards() {
+        for_each_observation(|state, observation, rng| {
+            let sample = observation.sample_state(rng);
+            assert!(count_cards(&sample).iter().all(|&count| count == 1));
+            assert_eq!(count_tokens(&sample), count_tokens(state));
+            for (player, sampled_player) in state.get_players().iter().zip(sample.get_players()) {
+                assert_eq!(player.get_production(), sampled_player.get_production());
+                assert_eq!(player.get_points(), sampled_player.get_points());
+                assert_eq!(player.reserved_blind, sampled_player.reserved_blind);
+                let deck = state.get_rules().deck();
+                let tiers = |player: &Player| player.get_reserve().iter().map(|&card_index| deck.get_card(card_index).tier().index()).collect::<Vec<_>>();
+                assert_eq!(tiers(player), tiers(sampled_player));
+            }
+            let observer = observation.get_observer_index();
+            assert!(state.get_players()[observer] == sample.get_players()[observer]);
+            for row_index in 0..3 {
+                let row = state.get_board().get_rows().get_row(row_index).unwrap();
+                let sampled_row = sample.get_board().get_rows().get_row(row_index).unwrap();
+                assert_eq!(row.visible, sampled_row.visible);
+                assert_eq!(row.hidden.len(), sampled_row.hidden.len());
+            }
+        });
+    }
+
+    #[test]
+    fn sampled_states_are_legal() {
+        for_each_observation(|state, observation, rng| {
+            let sample = observation.sample_state(rng);
+            assert_eq!(sample.get_zobrist_hash(), zobrist::compute(&sample));
+            for player in sample.get_players() {
+                assert!(player.get_reserve().len() <= sample.get_rules().max_reserve_cards() as usize);
+            }
+            let actions = sample.legal_actions();
+            if observation.get_observer_index() == state.get_current_player_index() {
+                assert_eq!(actions, state.legal_actions());
+            }
+            for action in actions {
+                let mut next = sample.clone();
+                next.apply(action).unwrap();
+            }
+        });
+    }
+}
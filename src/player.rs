@@ -1,116 +1,112 @@
-use crate::aristocrat::{Aristocrat, ARISTOCRAT_POINTS};
+use std::hash::{Hash, Hasher};
+use crate::aristocrat::ARISTOCRAT_POINTS;
 use crate::card::card::Card;
+use crate::deck::Deck;
 use crate::card::cost::Cost;
-use crate::resource::Resource;
-use crate::resources::{Resources, ResourcesBuilder};
+use crate::resources::Resources;
+use crate::rule_set::RuleSet;
 
+/// Cards and aristocrats are held as ids into the deck of the rules
 #[derive(Clone)]
 pub struct Player {
-    deck: Vec<&'static Card>,
-    resources: Resources,
-    reserve: Vec<&'static Card>,
-    aristocrats: Vec<&'static Aristocrat>,
+    pub(crate) deck: Vec<usize>,
+    pub(crate) resources: Resources,
+    pub(crate) reserve: Vec<usize>,
+    /// Whether each reserved card was taken face down from the hidden cards
+    pub(crate) reserved_blind: Vec<bool>,
+    pub(crate) aristocrats: Vec<usize>,
+    /// Kept up to date by `add_card`, `add_aristocrat` and their removals
+    pub(crate) production: Resources,
+    pub(crate) points: u8,
 }
 
-const MAX_RESERVE_CARDS: usize = 3;
 impl Player {
     pub fn new() -> Self {
         Self {
             deck: Vec::new(),
             resources: Resources::new(0, 0, 0, 0, 0, 0),
             reserve: Vec::new(),
+            reserved_blind: Vec::new(),
             aristocrats: Vec::new(),
+            production: Resources::new(0, 0, 0, 0, 0, 0),
+            points: 0,
         }
     }
+
     pub fn get_production(&self) -> Resources {
-        let mut resources_builder = ResourcesBuilder::default();
-        for card in &self.deck {
-            match card.production() {
-                Resource::Green => { resources_builder.n_green += 1}
-                Resource::Blue => { resources_builder.n_blue += 1}
-                Resource::Red => { resources_builder.n_red += 1}
-                Resource::White => { resources_builder.n_white += 1}
-                Resource::Black => { resources_builder.n_black += 1}
-            }
-        }
-        Resources::new(
-            resources_builder.n_green,
-            resources_builder.n_blue,
-            resources_builder.n_red,
-            resources_builder.n_white,
-            resources_builder.n_black,
-            0,
-        )
+        self.production.clone()
     }
-    pub fn get_resources(&self) -> &Resources {
-        &self.resources
+
+    pub(crate) fn add_card(&mut self, deck: &Deck, card_index: usize) {
+        let card = deck.get_card(card_index);
+        self.deck.push(card_index);
+        self.production = self.production.add(&Resources::single(card.production()));
+        self.points += card.n_points();
     }
-    pub fn get_reserve(&self) -> &Vec<&'static Card> {
-        &self.reserve
+
+    /// Takes back the card added last
+    pub(crate) fn remove_last_card(&mut self, deck: &Deck) -> Option<usize> {
+        let card_index = self.deck.pop()?;
+        let card = deck.get_card(card_index);
+        self.production = self.production.subtract(&Resources::single(card.production()));
+        self.points -= card.n_points();
+        Some(card_index)
     }
-    
-    pub fn get_points(&self) -> u8 {
-        let aristocrat_points = ARISTOCRAT_POINTS * self.aristocrats.len() as u8;
-        let mut card_points: u8 = 0;
-        for card in &self.deck {
-            card_points += card.n_points();
-        }
-        aristocrat_points + card_points
+
+    pub(crate) fn add_aristocrat(&mut self, aristocrat_index: usize) {
+        self.aristocrats.push(aristocrat_index);
+        self.points += ARISTOCRAT_POINTS;
     }
 
-    pub fn can_add_resources(&self, resources: &Resources) -> bool {
-        self.resources.sum() + resources.sum() <= 10
+    pub(crate) fn insert_into_reserve(&mut self, index: usize, card_index: usize, blind: bool) {
+        self.reserve.insert(index, card_index);
+        self.reserved_blind.insert(index, blind);
     }
 
-    pub fn can_add_reserve(&self) -> bool {
-        self.reserve.len() < MAX_RESERVE_CARDS
+    /// Removes the reserved card at the index, along with whether it was reserved blind
+    pub(crate) fn remove_from_reserve(&mut self, index: usize) -> (usize, bool) {
+        (self.reserve.remove(index), self.reserved_blind.remove(index))
     }
 
+    /// Takes back the aristocrat added last
+    pub(crate) fn remove_last_aristocrat(&mut self) -> Option<usize> {
+        let aristocrat = self.aristocrats.pop()?;
+        self.points -= ARISTOCRAT_POINTS;
+        Some(aristocrat)
+    }
 
-    pub fn to_builder(&self) -> PlayerBuilder {
-        PlayerBuilder::new(self)
+    pub fn get_resources(&self) -> &Resources {
+        &self.resources
+    }
+    /// Storage ids of the reserved cards, in the order they were reserved
+    pub fn get_reserve(&self) -> &Vec<usize> {
+        &self.reserve
     }
-}
 
-pub(crate) struct PlayerBuilder {
-    pub deck: Vec<&'static Card>,
-    pub resources: ResourcesBuilder,
-    pub reserve: Vec<&'static Card>,
-    pub aristocrats: Vec<&'static Aristocrat>,
-}
+    /// Whether the reserved card at the index was taken face down, hiding it from opponents
+    pub fn is_reserved_blind(&self, index: usize) -> bool {
+        self.reserved_blind.get(index).copied().unwrap_or(false)
+    }
+    /// Storage ids of the developed cards
+    pub fn get_deck(&self) -> &Vec<usize> {
+        &self.deck
+    }
 
-impl PlayerBuilder {
-    fn new(player: &Player) -> Self {
-        Self {
-            deck: player.deck.clone(),
-            resources: player.resources.to_builder(),
-            reserve: player.reserve.clone(),
-            aristocrats: player.aristocrats.clone(),
-        }
+    /// Storage ids of the aristocrats who visited the player
+    pub fn get_aristocrats(&self) -> &Vec<usize> {
+        &self.aristocrats
     }
 
-    pub(crate) fn get_production(&self) -> Resources {
-        let mut resources_builder = ResourcesBuilder::default();
-        for card in &self.deck {
-            match card.production() {
-                Resource::Green => { resources_builder.n_green += 1}
-                Resource::Blue => { resources_builder.n_blue += 1}
-                Resource::Red => { resources_builder.n_red += 1}
-                Resource::White => { resources_builder.n_white += 1}
-                Resource::Black => { resources_builder.n_black += 1}
-            }
-        }
-        Resources::new(
-            resources_builder.n_green,
-            resources_builder.n_blue,
-            resources_builder.n_red,
-            resources_builder.n_white,
-            resources_builder.n_black,
-            0,
-        )
-    }
-    
-    pub fn pay_for_card(&mut self, card: &'static Card) {
+    pub fn get_points(&self) -> u8 {
+        self.points
+    }
+
+    pub fn must_discard(&self, rules: &RuleSet) -> bool {
+        self.resources.sum() > rules.max_tokens()
+    }
+
+    /// Tokens spent on the card once production is deducted from its cost. Assuming can pay
+    pub fn get_payment(&self, card: &Card) -> Resources {
         let production = self.get_production();
         let remaining_cost = Cost::new(
             card.cost().n_green().saturating_sub(production.n_green()),
@@ -119,19 +115,39 @@ impl PlayerBuilder {
             card.cost().n_white().saturating_sub(production.n_white()),
             card.cost().n_black().saturating_sub(production.n_black()),
         );
-        self.resources.pay_cost(&remaining_cost)
+        self.resources.get_payment(&remaining_cost)
     }
 
-    pub fn add_resources(&mut self, resources: &ResourcesBuilder) {
-        self.resources.add(resources)
+    pub fn can_add_reserve(&self, rules: &RuleSet) -> bool {
+        self.reserve.len() < rules.max_reserve_cards() as usize
     }
-    
-    pub fn build(self) -> Player {
-        Player {
-            deck: self.deck,
-            resources: self.resources.build(),
-            reserve: self.reserve,
-            aristocrats: self.aristocrats,
-        }
+
+    /// Storage ids of the owned cards and aristocrats, sorted since the order they
+    /// were gained in does not matter, and of the reserved cards, in order
+    fn get_identity(&self) -> (Vec<usize>, Vec<usize>, Vec<(usize, bool)>) {
+        let mut deck = self.deck.clone();
+        deck.sort_unstable();
+        let mut aristocrats = self.aristocrats.clone();
+        aristocrats.sort_unstable();
+        let reserve = self.reserve.iter().copied().zip(self.reserved_blind.iter().copied()).collect();
+        (deck, aristocrats, reserve)
     }
-}
\ No newline at end of file
+}
+
+impl PartialEq for Player {
+    fn eq(&self, other: &Self) -> bool {
+        self.resources == other.resources
+            && self.production == other.production
+            && self.points == other.points
+            && self.get_identity() == other.get_identity()
+    }
+}
+
+impl Eq for Player {}
+
+impl Hash for Player {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.resources.hash(state);
+        self.get_identity().hash(state);
+    }
+}
@@ -1,8 +1,33 @@
-#[derive(PartialEq, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Resource {
     Green,
     Blue,
     Red,
     White,
     Black,
-}
\ No newline at end of file
+}
+
+/// Letter of gold tokens in the text notations
+pub const GOLD_LETTER: char = 'Y';
+
+impl Resource {
+    /// Colours in the order of `Resources::new`
+    pub const ALL: [Resource; 5] = [Resource::Green, Resource::Red, Resource::Blue, Resource::White, Resource::Black];
+
+    /// Letter of the colour in the text notations, K standing for black
+    pub fn to_letter(self) -> char {
+        match self {
+            Resource::Green => 'G',
+            Resource::Red => 'R',
+            Resource::Blue => 'B',
+            Resource::White => 'W',
+            Resource::Black => 'K',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Resource::ALL.into_iter().find(|resource| resource.to_letter() == letter.to_ascii_uppercase())
+    }
+}
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
 use crate::card::cost::Cost;
-#[derive(Clone)]
+use crate::resource::Resource;
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub struct Resources {
     n_green: u8,
     n_red: u8,
@@ -20,6 +22,32 @@ impl Resources {
             n_gold,
         }
     }
+    /// A single token of the resource
+    pub fn single(resource: Resource) -> Self {
+        match resource {
+            Resource::Green => Self::new(1, 0, 0, 0, 0, 0),
+            Resource::Red => Self::new(0, 1, 0, 0, 0, 0),
+            Resource::Blue => Self::new(0, 0, 1, 0, 0, 0),
+            Resource::White => Self::new(0, 0, 0, 1, 0, 0),
+            Resource::Black => Self::new(0, 0, 0, 0, 1, 0),
+        }
+    }
+
+    /// Tokens of the colour
+    pub fn get(&self, resource: Resource) -> u8 {
+        match resource {
+            Resource::Green => self.n_green,
+            Resource::Red => self.n_red,
+            Resource::Blue => self.n_blue,
+            Resource::White => self.n_white,
+            Resource::Black => self.n_black,
+        }
+    }
+
+    pub const fn single_gold() -> Self {
+        Self::new(0, 0, 0, 0, 0, 1)
+    }
+
     pub fn can_pay(&self, cost: &Cost) -> bool {
         self.get_n_missing_resources(cost) <= self.n_gold
     }
@@ -33,6 +61,19 @@ impl Resources {
 
 
 
+    /// Tokens spent on the cost: every colour as far as it goes, gold for the rest.
+    /// Assuming can pay
+    pub fn get_payment(&self, cost: &Cost) -> Self {
+        Self {
+            n_green: cost.n_green().min(self.n_green),
+            n_red: cost.n_red().min(self.n_red),
+            n_blue: cost.n_blue().min(self.n_blue),
+            n_white: cost.n_white().min(self.n_white),
+            n_black: cost.n_black().min(self.n_black),
+            n_gold: self.get_n_missing_resources(cost),
+        }
+    }
+
     pub fn add(&self, other: &Self) -> Self {
         Self {
             n_green: self.n_green + other.n_green,
@@ -44,10 +85,39 @@ impl Resources {
         }
     }
     
+    /// Assuming contains other
+    pub fn subtract(&self, other: &Self) -> Self {
+        Self {
+            n_green: self.n_green - other.n_green,
+            n_red: self.n_red - other.n_red,
+            n_blue: self.n_blue - other.n_blue,
+            n_black: self.n_black - other.n_black,
+            n_white: self.n_white - other.n_white,
+            n_gold: self.n_gold - other.n_gold,
+        }
+    }
+
     pub fn sum(&self) -> u8 {
         self.n_green + self.n_red + self.n_blue + self.n_white + self.n_black + self.n_gold
     }
 
+    /// Number of colours, gold excluded, with at least one token
+    pub fn n_colours(&self) -> u8 {
+        [self.n_green, self.n_red, self.n_blue, self.n_white, self.n_black]
+            .iter()
+            .filter(|&&n| n > 0)
+            .count() as u8
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.n_green >= other.n_green
+            && self.n_red >= other.n_red
+            && self.n_blue >= other.n_blue
+            && self.n_white >= other.n_white
+            && self.n_black >= other.n_black
+            && self.n_gold >= other.n_gold
+    }
+
     pub fn n_green(&self) -> u8 {
         self.n_green
     }
@@ -66,69 +136,4 @@ impl Resources {
     pub fn n_gold(&self) -> u8 {
         self.n_gold
     }
-    
-    pub fn to_builder(&self) -> ResourcesBuilder {
-        ResourcesBuilder::new(self)
-    }
-}
-
-#[derive(Default)]
-pub(crate) struct ResourcesBuilder {
-    pub n_green: u8,
-    pub n_red: u8,
-    pub n_blue: u8,
-    pub n_white: u8,
-    pub n_black: u8,
-    pub n_gold: u8,
-}
-
-impl ResourcesBuilder {
-    fn new(resources: &Resources) -> Self {
-        Self {
-            n_green: resources.n_green,
-            n_red: resources.n_red,
-            n_blue: resources.n_blue,
-            n_white: resources.n_white,
-            n_black: resources.n_black,
-            n_gold: resources.n_gold,
-        }
-    }
-
-    pub fn build(self) -> Resources {
-        Resources {
-            n_green: self.n_green,
-            n_red: self.n_red,
-            n_blue: self.n_blue,
-            n_white: self.n_white,
-            n_black: self.n_black,
-            n_gold: self.n_gold,
-        }
-    }
-    /// Assuming can pay
-    pub fn pay_cost(&mut self, cost: &Cost) {
-        let gold: u8 = self.n_gold - self.get_n_missing_resources(cost);
-        self.n_green = self.n_green.saturating_sub(cost.n_green());
-        self.n_red = self.n_red.saturating_sub(cost.n_red());
-        self.n_blue = self.n_blue.saturating_sub(cost.n_blue());
-        self.n_white = self.n_white.saturating_sub(cost.n_white());
-        self.n_black = self.n_black.saturating_sub(cost.n_black());
-        self.n_gold = gold;
-    }
-
-    pub fn add(&mut self, other: &Self) {
-        self.n_green += other.n_green;
-        self.n_red += other.n_red;
-        self.n_blue += other.n_blue;
-        self.n_black += other.n_black;
-        self.n_white += other.n_white;
-        self.n_gold += other.n_gold;
-    }
-
-    fn get_n_missing_resources(&self, cost: &Cost) -> u8 {
-        cost.n_green().saturating_sub(self.n_green)
-            + cost.n_red().saturating_sub(self.n_red)
-            + cost.n_blue().saturating_sub(self.n_blue)
-            + cost.n_black().saturating_sub(self.n_black)
-            + cost.n_white().saturating_sub(self.n_white)
-    }
 }
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use crate::deck::Deck;
+use crate::error::SplendorError;
+use crate::resource::Resource;
+
+/// Most aristocrats and reserved cards the move table has room for
+pub(crate) const MAX_ARISTOCRATS: u8 = 5;
+pub(crate) const MAX_RESERVE_CARDS: u8 = 3;
+
+/// Most tokens a game can hold, so that any count of them fits in a `u8`
+const MAX_TOKENS_IN_PLAY: u16 = u8::MAX as u16;
+
+/// Parameters of a game variant, the official rules and deck unless changed.
+/// The deck is saved apart, as the hash its ids refer to
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct RuleSet {
+    n_players: u8,
+    winning_points: u8,
+    max_tokens: u8,
+    max_reserve_cards: u8,
+    n_tokens_per_colour: u8,
+    n_gold: u8,
+    n_aristocrats: u8,
+    #[serde(skip, default = "Deck::official")]
+    deck: &'static Deck,
+}
+
+impl RuleSet {
+    pub fn official(n_players: u8) -> Result<Self, SplendorError> {
+        let n_tokens_per_colour = match n_players {
+            2 => 4,
+            3 => 5,
+            4 => 7,
+            _ => return Err(SplendorError::InvalidPlayerCount(n_players)),
+        };
+        Ok(Self {
+            n_players,
+            winning_points: 15,
+            max_tokens: 10,
+            max_reserve_cards: MAX_RESERVE_CARDS,
+            n_tokens_per_colour,
+            n_gold: 5,
+            n_aristocrats: n_players + 1,
+            deck: Deck::official(),
+        })
+    }
+
+    pub fn with_winning_points(mut self, winning_points: u8) -> Self {
+        self.winning_points = winning_points;
+        self
+    }
+
+    /// The hand limit cannot be above the tokens in play, so set the bank first
+    pub fn with_max_tokens(mut self, max_tokens: u8) -> Result<Self, SplendorError> {
+        if max_tokens as u16 > self.n_tokens_in_play() {
+            return Err(SplendorError::InvalidRule(format!("A hand limit of {} is above the {} tokens in play", max_tokens, self.n_tokens_in_play())));
+        }
+        self.max_tokens = max_tokens;
+        Ok(self)
+    }
+
+    pub fn with_max_reserve_cards(mut self, max_reserve_cards: u8) -> Result<Self, SplendorError> {
+        if max_reserve_cards > MAX_RESERVE_CARDS {
+            return Err(SplendorError::InvalidRule(format!("At most {} reserved cards are supported", MAX_RESERVE_CARDS)));
+        }
+        self.max_reserve_cards = max_reserve_cards;
+        Ok(self)
+    }
+
+    pub fn with_n_tokens_per_colour(mut self, n_tokens_per_colour: u8) -> Result<Self, SplendorError> {
+        self.n_tokens_per_colour = n_tokens_per_colour;
+        self.check_tokens_in_play()
+    }
+
+    pub fn with_n_gold(mut self, n_gold: u8) -> Result<Self, SplendorError> {
+        self.n_gold = n_gold;
+        self.check_tokens_in_play()
+    }
+
+    /// Coloured tokens and gold together
+    fn n_tokens_in_play(&self) -> u16 {
+        Resource::ALL.len() as u16 * self.n_tokens_per_colour as u16 + self.n_gold as u16
+    }
+
+    fn check_tokens_in_play(self) -> Result<Self, SplendorError> {
+        if self.n_tokens_in_play() > MAX_TOKENS_IN_PLAY {
+            return Err(SplendorError::InvalidRule(format!("At most {} tokens in play are supported, got {}", MAX_TOKENS_IN_PLAY, self.n_tokens_in_play())));
+        }
+        Ok(self)
+    }
+
+    pub fn with_n_aristocrats(mut self, n_aristocrats: u8) -> Result<Self, SplendorError> {
+        if n_aristocrats > MAX_ARISTOCRATS {
+            return Err(SplendorError::InvalidRule(format!("At most {} aristocrats are supported", MAX_ARISTOCRATS)));
+        }
+        self.n_aristocrats = n_aristocrats;
+        Ok(self)
+    }
+
+    /// Plays with the deck, see `Deck::register` for one read from JSON
+    pub fn with_deck(mut self, deck: &'static Deck) -> Self {
+        self.deck = deck;
+        self
+    }
+
+    /// Checks rules that did not go through the setters, such as saved ones
+    pub(crate) fn check(&self) -> Result<(), SplendorError> {
+        RuleSet::official(self.n_players)?;
+        self.check_tokens_in_play()?
+            .with_max_tokens(self.max_tokens)?
+            .with_max_reserve_cards(self.max_reserve_cards)?
+            .with_n_aristocrats(self.n_aristocrats)?;
+        Ok(())
+    }
+
+    pub fn n_players(&self) -> u8 {
+        self.n_players
+    }
+    pub fn winning_points(&self) -> u8 {
+        self.winning_points
+    }
+    pub fn max_tokens(&self) -> u8 {
+        self.max_tokens
+    }
+    pub fn max_reserve_cards(&self) -> u8 {
+        self.max_reserve_cards
+    }
+    pub fn n_tokens_per_colour(&self) -> u8 {
+        self.n_tokens_per_colour
+    }
+    pub fn n_gold(&self) -> u8 {
+        self.n_gold
+    }
+    pub fn n_aristocrats(&self) -> u8 {
+        self.n_aristocrats
+    }
+    pub fn deck(&self) -> &'static Deck {
+        self.deck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_in_play_fit_in_a_byte() {
+        let rules = RuleSet::official(4).unwrap();
+        assert!(rules.with_n_tokens_per_colour(50).is_ok());
+        assert!(rules.with_n_tokens_per_colour(51).is_err());
+        assert!(rules.with_n_gold(220).is_ok());
+        assert!(rules.with_n_gold(221).is_err());
+        assert!(rules.with_n_tokens_per_colour(50).unwrap().with_n_gold(6).is_err());
+    }
+
+    #[test]
+    fn hand_limit_is_within_the_tokens_in_play() {
+        let rules = RuleSet::official(2).unwrap();
+        assert!(rules.with_max_tokens(25).is_ok());
+        assert!(rules.with_max_tokens(26).is_err());
+        assert!(rules.with_n_gold(6).unwrap().with_max_tokens(26).is_ok());
+    }
+}
@@ -0,0 +1,412 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use crate::board::board::Board;
+use crate::board::rows::row::{Row, CARDS_PER_ROW};
+use crate::board::rows::rows::Rows;
+use crate::deck::Deck;
+use crate::error::SplendorError;
+use crate::game_state::{GameState, TurnPhase};
+use crate::player::Player;
+use crate::resource::Resource;
+use crate::resources::Resources;
+use crate::rule_set::RuleSet;
+
+/// Bumped whenever the saved layout of a `GameState` changes
+pub const STATE_FORMAT_VERSION: u32 = 2;
+
+pub(crate) fn check_card_id(deck: &Deck, card_index: usize, to_error: fn(String) -> SplendorError) -> Result<usize, SplendorError> {
+    if card_index >= deck.n_cards() {
+        return Err(to_error(format!("unknown card id {}", card_index)));
+    }
+    Ok(card_index)
+}
+
+pub(crate) fn check_aristocrat_id(deck: &Deck, aristocrat_index: usize, to_error: fn(String) -> SplendorError) -> Result<usize, SplendorError> {
+    if aristocrat_index >= deck.n_aristocrats() {
+        return Err(to_error(format!("unknown aristocrat id {}", aristocrat_index)));
+    }
+    Ok(aristocrat_index)
+}
+
+/// The registered deck a saved state, notation or record names by its hash
+pub(crate) fn find_deck(hash: u64, to_error: fn(String) -> SplendorError) -> Result<&'static Deck, SplendorError> {
+    Deck::find(hash).ok_or_else(|| to_error(format!("deck {:016x} is not registered", hash)))
+}
+
+/// Checks what the types alone cannot: the rules are supported, the players match them,
+/// the current player exists, no more tokens are held than the rules put in play, reserves
+/// and aristocrats fit the rules, every row has its four slots and only cards of its tier,
+/// every card and aristocrat id is known, each card and aristocrat used at most once,
+/// and the phase agrees with the payment, the tokens and the aristocrats of the current player
+pub(crate) fn check_state(game_state: &GameState, to_error: fn(String) -> SplendorError) -> Result<(), SplendorError> {
+    let rules = &game_state.rules;
+    rules.check().map_err(|error| to_error(error.to_string()))?;
+    if game_state.players.len() != rules.n_players() as usize {
+        return Err(to_error("the number of players does not match the rules".to_string()));
+    }
+    if game_state.current_player_index >= game_state.players.len() {
+        return Err(SplendorError::InvalidPlayerIndex(game_state.current_player_index));
+    }
+    check_tokens(game_state, to_error)?;
+    for (index, player) in game_state.players.iter().enumerate() {
+        if player.reserve.len() > rules.max_reserve_cards() as usize {
+            return Err(to_error(format!("player {} reserved {} cards, at most {} are allowed", index + 1, player.reserve.len(), rules.max_reserve_cards())));
+        }
+    }
+    if game_state.board.aristocrats.len() > rules.n_aristocrats() as usize {
+        return Err(to_error(format!("{} aristocrats are on the board, at most {} are allowed", game_state.board.aristocrats.len(), rules.n_aristocrats())));
+    }
+    let deck = game_state.rules.deck();
+    let mut card_indices = Vec::new();
+    for row_index in 0..3 {
+        let row = game_state.board.rows.get_row(row_index)?;
+        if row.visible.len() != CARDS_PER_ROW {
+            return Err(to_error(format!("row {} has {} visible slots, expected {}", row_index + 1, row.visible.len(), CARDS_PER_ROW)));
+        }
+        for &card_index in row.visible.iter().flatten().chain(&row.hidden) {
+            let tier = deck.get_card(check_card_id(deck, card_index, to_error)?).tier();
+            if tier.number() != row_index + 1 {
+                return Err(to_error(format!("card id {} of tier {} lies in row {}", card_index, tier.number(), row_index + 1)));
+            }
+            card_indices.push(card_index);
+        }
+    }
+    for player in &game_state.players {
+        card_indices.extend(&player.deck);
+        card_indices.extend(&player.reserve);
+    }
+    let mut seen = HashSet::new();
+    for card_index in card_indices {
+        if !seen.insert(check_card_id(deck, card_index, to_error)?) {
+            return Err(to_error(format!("card id {} appears twice", card_index)));
+        }
+    }
+    let player_aristocrats = game_state.players
+        .iter()
+        .flat_map(|player| player.aristocrats.iter().copied());
+    let mut seen = HashSet::new();
+    for aristocrat_index in game_state.board.aristocrats.iter().copied().chain(player_aristocrats) {
+        if !seen.insert(check_aristocrat_id(deck, aristocrat_index, to_error)?) {
+            return Err(to_error(format!("aristocrat id {} appears twice", aristocrat_index)));
+        }
+    }
+    check_phase(game_state, to_error)
+}
+
+/// Every kind of token is held at most as many times as the rules put it in play,
+/// so that no count or sum of them overflows
+fn check_tokens(game_state: &GameState, to_error: fn(String) -> SplendorError) -> Result<(), SplendorError> {
+    let holdings = || std::iter::once(&game_state.board.resources).chain(game_state.players.iter().map(|player| &player.resources));
+    let n_gold: u16 = holdings().map(|resources| resources.n_gold() as u16).sum();
+    if n_gold > game_state.rules.n_gold() as u16 {
+        return Err(to_error(format!("{} gold tokens are held, {} are in play", n_gold, game_state.rules.n_gold())));
+    }
+    for resource in Resource::ALL {
+        let n_tokens: u16 = holdings().map(|resources| resources.get(resource) as u16).sum();
+        if n_tokens > game_state.rules.n_tokens_per_colour() as u16 {
+            return Err(to_error(format!("{} {:?} tokens are held, {} are in play", n_tokens, resource, game_state.rules.n_tokens_per_colour())));
+        }
+    }
+    Ok(())
+}
+
+/// The payment is only pending, with coloured tokens back in the bank and gold to spend, while paying.
+/// Discarding needs too many tokens and choosing needs several aristocrats to qualify
+fn check_phase(game_state: &GameState, to_error: fn(String) -> SplendorError) -> Result<(), SplendorError> {
+    let player = game_state.get_current_player();
+    let payment = &game_state.payment;
+    let has_payment = *payment != Resources::new(0, 0, 0, 0, 0, 0);
+    let is_consistent = match game_state.phase {
+        TurnPhase::Payment => has_payment
+            && payment.n_gold() == 0
+            && game_state.board.resources.contains(payment)
+            && player.resources.n_gold() > 0,
+        TurnPhase::Discard => !has_payment && player.must_discard(&game_state.rules),
+        TurnPhase::ChooseAristocrat => !has_payment && game_state.get_qualifying_aristocrats().len() > 1,
+        TurnPhase::Action | TurnPhase::GameOver => !has_payment,
+    };
+    if !is_consistent {
+        return Err(to_error(format!("the {:?} phase does not match the payment, tokens or aristocrats of the current player", game_state.phase)));
+    }
+    Ok(())
+}
+
+/// Rows are saved as a list of the first, second and third tier
+impl From<Rows> for [Row; 3] {
+    fn from(rows: Rows) -> Self {
+        [0, 1, 2].map(|index| rows.get_row(index).expect("Rows hold every tier").clone())
+    }
+}
+
+impl From<[Row; 3]> for Rows {
+    fn from(rows: [Row; 3]) -> Self {
+        Rows::from_rows(rows)
+    }
+}
+
+/// A player with cards and aristocrats saved as storage ids.
+/// Production and points are recomputed from the deck of the state when loading
+#[derive(Serialize, Deserialize)]
+struct PlayerRecord {
+    resources: Resources,
+    deck: Vec<usize>,
+    reserve: Vec<usize>,
+    reserved_blind: Vec<bool>,
+    aristocrats: Vec<usize>,
+}
+
+impl From<Player> for PlayerRecord {
+    fn from(player: Player) -> Self {
+        Self {
+            resources: player.resources,
+            deck: player.deck,
+            reserve: player.reserve,
+            reserved_blind: player.reserved_blind,
+            aristocrats: player.aristocrats,
+        }
+    }
+}
+
+impl PlayerRecord {
+    fn to_player(self, deck: &Deck) -> Result<Player, SplendorError> {
+        if self.reserve.len() != self.reserved_blind.len() {
+            return Err(SplendorError::InvalidSavedState("one blind flag is needed per reserved card".to_string()));
+        }
+        let mut player = Player::new();
+        player.resources = self.resources;
+        for card_index in self.deck {
+            player.add_card(deck, check_card_id(deck, card_index, SplendorError::InvalidSavedState)?);
+        }
+        for (index, (card_index, blind)) in self.reserve.into_iter().zip(self.reserved_blind).enumerate() {
+            player.insert_into_reserve(index, check_card_id(deck, card_index, SplendorError::InvalidSavedState)?, blind);
+        }
+        for aristocrat_index in self.aristocrats {
+            player.add_aristocrat(check_aristocrat_id(deck, aristocrat_index, SplendorError::InvalidSavedState)?);
+        }
+        Ok(player)
+    }
+}
+
+/// A game state tagged with the format version and the hash of the deck its ids refer to.
+/// The Zobrist hash is recomputed when loading
+#[derive(Serialize, Deserialize)]
+pub(crate) struct GameStateRecord {
+    version: u32,
+    deck: u64,
+    players: Vec<PlayerRecord>,
+    current_player_index: usize,
+    board: Board,
+    phase: TurnPhase,
+    n_consecutive_passes: u8,
+    payment: Resources,
+    rules: RuleSet,
+}
+
+impl From<GameState> for GameStateRecord {
+    fn from(game_state: GameState) -> Self {
+        Self {
+            version: STATE_FORMAT_VERSION,
+            deck: game_state.rules.deck().get_hash(),
+            players: game_state.players.into_iter().map(PlayerRecord::from).collect(),
+            current_player_index: game_state.current_player_index,
+            board: game_state.board,
+            phase: game_state.phase,
+            n_consecutive_passes: game_state.n_consecutive_passes,
+            payment: game_state.payment,
+            rules: game_state.rules,
+        }
+    }
+}
+
+impl TryFrom<GameStateRecord> for GameState {
+    type Error = SplendorError;
+
+    fn try_from(record: GameStateRecord) -> Result<Self, Self::Error> {
+        check_version(record.version)?;
+        let deck = find_deck(record.deck, SplendorError::InvalidSavedState)?;
+        let game_state = GameState {
+            players: record.players.into_iter().map(|player| player.to_player(deck)).collect::<Result<_, _>>()?,
+            current_player_index: record.current_player_index,
+            board: record.board,
+            phase: record.phase,
+            n_consecutive_passes: record.n_consecutive_passes,
+            payment: record.payment,
+            rules: record.rules.with_deck(deck),
+            zobrist_hash: 0,
+        };
+        check_state(&game_state, SplendorError::InvalidSavedState)?;
+        Ok(game_state.with_zobrist_hash())
+    }
+}
+
+fn check_version(version: u32) -> Result<(), SplendorError> {
+    if version != STATE_FORMAT_VERSION {
+        return Err(SplendorError::InvalidSavedState(format!("format version {} is not supported, expected {}", version, STATE_FORMAT_VERSION)));
+    }
+    Ok(())
+}
+
+/// Only the version, read first so that states saved in another format give a clear error
+#[derive(Deserialize)]
+struct VersionTag {
+    version: u32,
+}
+
+impl GameState {
+    pub fn to_json(&self) -> Result<String, SplendorError> {
+        serde_json::to_string(self).map_err(|error| SplendorError::InvalidSavedState(error.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SplendorError> {
+        let tag: VersionTag = serde_json::from_str(json).map_err(|error| SplendorError::InvalidSavedState(error.to_string()))?;
+        check_version(tag.version)?;
+        let record: GameStateRecord = serde_json::from_str(json).map_err(|error| SplendorError::InvalidSavedState(error.to_string()))?;
+        GameState::try_from(record)
+    }
+
+    pub fn to_bincode(&self) -> Result<Vec<u8>, SplendorError> {
+        bincode::serialize(self).map_err(|error| SplendorError::InvalidSavedState(error.to_string()))
+    }
+
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, SplendorError> {
+        // The version is the first field, so it can be read on its own
+        let version: u32 = bincode::deserialize(bytes).map_err(|error| SplendorError::InvalidSavedState(error.to_string()))?;
+        check_version(version)?;
+        let record: GameStateRecord = bincode::deserialize(bytes).map_err(|error| SplendorError::InvalidSavedState(error.to_string()))?;
+        GameState::try_from(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::create_initial_game_state;
+    use crate::rule_set::{MAX_ARISTOCRATS, MAX_RESERVE_CARDS};
+    use crate::testing::random_games;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn initial_state() -> GameState {
+        create_initial_game_state(RuleSet::official(2).unwrap(), &mut ChaCha8Rng::seed_from_u64(0))
+    }
+
+    fn is_saved_state_error<T>(result: Result<T, SplendorError>) -> bool {
+        matches!(result, Err(SplendorError::InvalidSavedState(_)))
+    }
+
+    #[test]
+    fn states_round_trip() {
+        for state in random_games(6).flat_map(|record| record.states()) {
+            for loaded in [GameState::from_json(&state.to_json().unwrap()).unwrap(), GameState::from_bincode(&state.to_bincode().unwrap()).unwrap()] {
+                assert!(loaded == state);
+                assert_eq!(loaded.get_zobrist_hash(), state.get_zobrist_hash());
+                assert_eq!(loaded.to_notation(), state.to_notation());
+            }
+        }
+    }
+
+    #[test]
+    fn other_format_versions_are_rejected() {
+        let state = initial_state();
+        let mut json: serde_json::Value = serde_json::from_str(&state.to_json().unwrap()).unwrap();
+        json["version"] = (STATE_FORMAT_VERSION + 1).into();
+        assert!(is_saved_state_error(GameState::from_json(&json.to_string())));
+        let mut bytes = state.to_bincode().unwrap();
+        bytes[..4].copy_from_slice(&(STATE_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(is_saved_state_error(GameState::from_bincode(&bytes)));
+    }
+
+    #[test]
+    fn states_of_unregistered_decks_are_rejected() {
+        let mut json: serde_json::Value = serde_json::from_str(&initial_state().to_json().unwrap()).unwrap();
+        json["deck"] = (Deck::official().get_hash() ^ 1).into();
+        assert!(is_saved_state_error(GameState::from_json(&json.to_string())));
+    }
+
+    /// Saved states and positions both go through `check_state`
+    fn is_rejected(state: &GameState) -> bool {
+        is_saved_state_error(GameState::from_json(&state.to_json().unwrap()))
+            && is_saved_state_error(GameState::from_bincode(&state.to_bincode().unwrap()))
+            && matches!(GameState::from_notation(&state.to_notation()), Err(SplendorError::InvalidNotation(_)))
+    }
+
+    #[test]
+    fn unsupported_rules_are_rejected() {
+        let json: serde_json::Value = serde_json::from_str(&initial_state().to_json().unwrap()).unwrap();
+        for (rule, value) in [("n_tokens_per_colour", 60), ("n_gold", 250), ("max_tokens", 30), ("max_reserve_cards", 4), ("n_aristocrats", 6)] {
+            let mut json = json.clone();
+            json["rules"][rule] = value.into();
+            assert!(is_saved_state_error(GameState::from_json(&json.to_string())), "{} of {} was accepted", rule, value);
+        }
+    }
+
+    #[test]
+    fn tokens_beyond_those_in_play_are_rejected() {
+        let mut extra_gold = initial_state();
+        extra_gold.players[0].resources = Resources::single_gold();
+        let mut overflowing = initial_state();
+        overflowing.players[1].resources = Resources::new(200, 200, 0, 0, 0, 0);
+        assert!(is_rejected(&extra_gold));
+        assert!(is_rejected(&overflowing));
+    }
+
+    #[test]
+    fn overfull_reserves_are_rejected() {
+        let mut state = initial_state();
+        for _ in 0..=MAX_RESERVE_CARDS {
+            let card_index = state.board.rows.get_row_mut(0).unwrap().hidden.pop().unwrap();
+            let player = &mut state.players[0];
+            player.insert_into_reserve(player.reserve.len(), card_index, true);
+        }
+        assert!(is_rejected(&state));
+    }
+
+    #[test]
+    fn extra_board_aristocrats_are_rejected() {
+        let mut state = initial_state();
+        let unused: Vec<usize> = (0..Deck::official().n_aristocrats()).filter(|index| !state.board.aristocrats.contains(index)).collect();
+        state.board.aristocrats = unused[..=MAX_ARISTOCRATS as usize].to_vec();
+        assert!(is_rejected(&state));
+    }
+
+    #[test]
+    fn phases_must_match_the_state() {
+        let mut payment_without_gold = initial_state();
+        payment_without_gold.phase = TurnPhase::Payment;
+        payment_without_gold.payment = Resources::single(Resource::Red);
+
+        let mut payment_outside_payment = payment_without_gold.clone();
+        payment_outside_payment.phase = TurnPhase::Action;
+
+        let mut discard_under_limit = initial_state();
+        discard_under_limit.phase = TurnPhase::Discard;
+
+        let mut choice_without_aristocrats = initial_state();
+        choice_without_aristocrats.phase = TurnPhase::ChooseAristocrat;
+
+        for state in [payment_without_gold, payment_outside_payment, discard_under_limit, choice_without_aristocrats] {
+            assert!(is_rejected(&state), "{} was accepted", state.to_notation());
+        }
+    }
+
+    #[test]
+    fn inconsistent_states_are_rejected() {
+        let mut missing_slot = initial_state();
+        missing_slot.board.rows.get_row_mut(0).unwrap().visible.pop();
+
+        let mut wrong_tier = initial_state();
+        let first_card = wrong_tier.board.rows.get_row(0).unwrap().visible[0];
+        let second_card = wrong_tier.board.rows.get_row(1).unwrap().visible[0];
+        wrong_tier.board.rows.get_row_mut(0).unwrap().visible[0] = second_card;
+        wrong_tier.board.rows.get_row_mut(1).unwrap().visible[0] = first_card;
+
+        let mut shared_aristocrat = initial_state();
+        let aristocrat_index = shared_aristocrat.board.aristocrats[0];
+        shared_aristocrat.players[1].add_aristocrat(aristocrat_index);
+
+        for state in [missing_slot, wrong_tier, shared_aristocrat] {
+            assert!(is_saved_state_error(GameState::from_json(&state.to_json().unwrap())));
+            assert!(is_saved_state_error(GameState::from_bincode(&state.to_bincode().unwrap())));
+        }
+    }
+}
@@ -1,58 +1,71 @@
+use crate::board::rows::row::CARDS_PER_ROW;
 use crate::card::card::Card;
-use crate::card::card_storage::CARD_STORAGE;
-use crate::board::rows::rows::Rows;
+use crate::deck::Deck;
+use crate::game_state::TurnPhase;
+use crate::observation::Observation;
 use crate::resource::Resource;
 
-const TOTAL_CARDS: usize = 90;
-const CARDS_PER_ROW: usize = 4;
 const CARD_PARAMS_SIZE: usize = 11;
 
+/// One entry per turn phase, then the coloured tokens of the pending payment
+pub const TURN_ENCODING_SIZE: usize = 10;
+
+/// The phase, which decides the moves open to the player, one-hot,
+/// then the payment gold can still replace, the same for every encoder
+pub fn encode_turn(observation: &Observation) -> Vec<u8> {
+    let mut encoding = vec![0u8; TURN_ENCODING_SIZE];
+    let phase_index = match observation.get_phase() {
+        TurnPhase::Action => 0,
+        TurnPhase::Discard => 1,
+        TurnPhase::Payment => 2,
+        TurnPhase::ChooseAristocrat => 3,
+        TurnPhase::GameOver => 4,
+    };
+    encoding[phase_index] = 1;
+    let payment = observation.get_payment();
+    encoding[5..].copy_from_slice(&[payment.n_green(), payment.n_red(), payment.n_blue(), payment.n_black(), payment.n_white()]);
+    encoding
+}
+
+/// Encoders only see an `Observation`, so features never include hidden information
 pub trait StateEncoder: Send + Sync {
-    fn encode_rows(&self, rows: &Rows) -> Vec<u8>;
+    fn encode_rows(&self, observation: &Observation) -> Vec<u8>;
     fn rows_encoding_size(&self) -> usize;
     fn clone_box(&self) -> Box<dyn StateEncoder>;
 }
 
-pub struct OneHotCardEncoder;
+/// One entry per card of the deck the encoder was made for
+pub struct OneHotCardEncoder {
+    n_cards: usize,
+}
 
 impl OneHotCardEncoder {
-    pub fn new() -> Self {
-        Self
-    }
-    fn get_card_index(card: &Card) -> Option<usize> {
-        let card_ptr = card as *const Card;
-        for i in 0..TOTAL_CARDS {
-            let storage_card = CARD_STORAGE.get_card(i);
-            let storage_ptr = storage_card as *const Card;
-            if card_ptr == storage_ptr {
-                return Some(i);
-            }
-        }
-        None
+    pub fn new(deck: &Deck) -> Self {
+        Self { n_cards: deck.n_cards() }
     }
 }
 
 impl StateEncoder for OneHotCardEncoder {
-    fn encode_rows(&self, rows: &Rows) -> Vec<u8> {
-        let mut encoding = vec![0u8; TOTAL_CARDS];
+    fn encode_rows(&self, observation: &Observation) -> Vec<u8> {
+        debug_assert_eq!(observation.get_rules().deck().n_cards(), self.n_cards, "Observed game uses the deck of the encoder");
+        let mut encoding = vec![0u8; self.n_cards];
         for row_index in 0..3 {
-            let row = rows.get_row(row_index);
+            let Ok(row) = observation.get_row(row_index) else {
+                continue;
+            };
             for position in 0..CARDS_PER_ROW {
-                if row.has_card(position) {
-                    let card = row.get_card(position);
-                    if let Some(card_index) = Self::get_card_index(card) {
-                        encoding[card_index] = 1;
-                    }
+                if let Some(card_index) = row.get_card_index(position) {
+                    encoding[card_index] = 1;
                 }
             }
         }
         encoding
     }
     fn rows_encoding_size(&self) -> usize {
-        TOTAL_CARDS
+        self.n_cards
     }
     fn clone_box(&self) -> Box<dyn StateEncoder> {
-        Box::new(OneHotCardEncoder)
+        Box::new(OneHotCardEncoder { n_cards: self.n_cards })
     }
 }
 
@@ -86,17 +99,14 @@ impl ParameterEncoder {
 }
 
 impl StateEncoder for ParameterEncoder {
-    fn encode_rows(&self, rows: &Rows) -> Vec<u8> {
+    fn encode_rows(&self, observation: &Observation) -> Vec<u8> {
         let mut encoding = Vec::with_capacity(CARD_PARAMS_SIZE * CARDS_PER_ROW * 3);
         for row_index in 0..3 {
-            let row = rows.get_row(row_index);
+            let Ok(row) = observation.get_row(row_index) else {
+                continue;
+            };
             for position in 0..CARDS_PER_ROW {
-                if row.has_card(position) {
-                    let card = row.get_card(position);
-                    encoding.extend(Self::encode_card(Some(card)));
-                } else {
-                    encoding.extend(Self::encode_card(None));
-                }
+                encoding.extend(Self::encode_card(row.get_card(position)));
             }
         }
         encoding
@@ -0,0 +1,32 @@
+//! Games shared by the unit tests
+
+use crate::deck::Deck;
+use crate::game_record::GameRecord;
+use crate::rule_set::RuleSet;
+use rand::prelude::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Random games of two to four players, one per seed from 0. Hidden cards are drawn at random,
+/// so the moves that draw one carry a chance outcome
+pub(crate) fn random_games(n_games: u64) -> impl Iterator<Item = GameRecord> {
+    (0..n_games).map(|seed| {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let rules = RuleSet::official(2 + (seed % 3) as u8).unwrap();
+        let mut record = GameRecord::from_seed(rules, seed);
+        while !record.get_final_state().is_game_over() {
+            let actions = record.get_final_state().legal_actions();
+            let action = actions[rng.gen_range(0..actions.len())];
+            let outcome = record.get_final_state().chance_outcomes(action).choose(&mut rng).copied();
+            record.push(action, outcome).unwrap();
+        }
+        record
+    })
+}
+
+/// The official deck without its last card, registered so that games played with it can be read back
+pub(crate) fn custom_deck() -> &'static Deck {
+    let mut json: serde_json::Value = serde_json::from_str(&Deck::official().to_json()).unwrap();
+    json["cards"].as_array_mut().unwrap().pop();
+    Deck::from_json(&json.to_string()).unwrap().register()
+}
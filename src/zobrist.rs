@@ -0,0 +1,120 @@
+use crate::game_state::{GameState, TurnPhase};
+use crate::resources::Resources;
+
+/// Kinds of state features, each gets its own family of keys
+const PLAYER_TOKENS: u64 = 1;
+const BANK_TOKENS: u64 = 2;
+const PAYMENT_TOKENS: u64 = 3;
+const OWNED_CARD: u64 = 4;
+const RESERVED_CARD: u64 = 5;
+const PLAYER_ARISTOCRAT: u64 = 6;
+const VISIBLE_CARD: u64 = 7;
+const HIDDEN_CARD: u64 = 8;
+const BOARD_ARISTOCRAT: u64 = 9;
+const CURRENT_PLAYER: u64 = 10;
+const PHASE: u64 = 11;
+const CONSECUTIVE_PASSES: u64 = 12;
+
+/// Pseudo-random key of a feature, derived on the fly so that no table limits the number
+/// of cards or tokens
+fn key(kind: u64, a: usize, b: usize, c: usize) -> u64 {
+    let mut x = (kind << 56) ^ ((a as u64) << 40) ^ ((b as u64) << 20) ^ c as u64;
+    // splitmix64 finaliser
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn tokens(kind: u64, owner: usize, resources: &Resources) -> u64 {
+    let counts = [resources.n_green(), resources.n_red(), resources.n_blue(), resources.n_white(), resources.n_black(), resources.n_gold()];
+    counts.iter().enumerate().fold(0, |hash, (colour, &count)| hash ^ key(kind, owner, colour, count as usize))
+}
+
+pub(crate) fn player_tokens(player_index: usize, resources: &Resources) -> u64 {
+    tokens(PLAYER_TOKENS, player_index, resources)
+}
+
+pub(crate) fn bank_tokens(resources: &Resources) -> u64 {
+    tokens(BANK_TOKENS, 0, resources)
+}
+
+pub(crate) fn payment_tokens(resources: &Resources) -> u64 {
+    tokens(PAYMENT_TOKENS, 0, resources)
+}
+
+pub(crate) fn owned_card(player_index: usize, card_index: usize) -> u64 {
+    key(OWNED_CARD, player_index, card_index, 0)
+}
+
+pub(crate) fn reserved_card(player_index: usize, card_index: usize) -> u64 {
+    key(RESERVED_CARD, player_index, card_index, 0)
+}
+
+pub(crate) fn player_aristocrat(player_index: usize, aristocrat_index: usize) -> u64 {
+    key(PLAYER_ARISTOCRAT, player_index, aristocrat_index, 0)
+}
+
+pub(crate) fn visible_card(row: usize, position: usize, card_index: usize) -> u64 {
+    key(VISIBLE_CARD, row, position, card_index)
+}
+
+/// Hidden cards are keyed by their distance from the bottom of the row, which does not
+/// change when the top card is drawn
+pub(crate) fn hidden_card(row: usize, depth_from_bottom: usize, card_index: usize) -> u64 {
+    key(HIDDEN_CARD, row, depth_from_bottom, card_index)
+}
+
+pub(crate) fn board_aristocrat(aristocrat_index: usize) -> u64 {
+    key(BOARD_ARISTOCRAT, aristocrat_index, 0, 0)
+}
+
+pub(crate) fn current_player(player_index: usize) -> u64 {
+    key(CURRENT_PLAYER, player_index, 0, 0)
+}
+
+pub(crate) fn phase(phase: TurnPhase) -> u64 {
+    key(PHASE, phase as usize, 0, 0)
+}
+
+pub(crate) fn consecutive_passes(n_consecutive_passes: u8) -> u64 {
+    key(CONSECUTIVE_PASSES, n_consecutive_passes as usize, 0, 0)
+}
+
+/// Zobrist hash of the whole state, computed from scratch
+pub(crate) fn compute(game_state: &GameState) -> u64 {
+    let mut hash = current_player(game_state.current_player_index)
+        ^ phase(game_state.phase)
+        ^ consecutive_passes(game_state.n_consecutive_passes)
+        ^ payment_tokens(&game_state.payment)
+        ^ bank_tokens(&game_state.board.resources);
+    for (player_index, player) in game_state.players.iter().enumerate() {
+        hash ^= player_tokens(player_index, &player.resources);
+        for &card_index in &player.deck {
+            hash ^= owned_card(player_index, card_index);
+        }
+        for &card_index in &player.reserve {
+            hash ^= reserved_card(player_index, card_index);
+        }
+        for &aristocrat_index in &player.aristocrats {
+            hash ^= player_aristocrat(player_index, aristocrat_index);
+        }
+    }
+    for row_index in 0..3u8 {
+        let Ok(row) = game_state.board.rows.get_row(row_index) else {
+            continue;
+        };
+        for (position, card_index) in row.visible.iter().enumerate() {
+            if let Some(card_index) = card_index {
+                hash ^= visible_card(row_index as usize, position, *card_index);
+            }
+        }
+        for (depth, &card_index) in row.hidden.iter().rev().enumerate() {
+            hash ^= hidden_card(row_index as usize, depth, card_index);
+        }
+    }
+    for &aristocrat_index in &game_state.board.aristocrats {
+        hash ^= board_aristocrat(aristocrat_index);
+    }
+    hash
+}
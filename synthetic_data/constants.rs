@@ -1,3 +0,0 @@
-/// The number of points required to win the game
-pub const WINNING_POINTS: u8 = 15;
-
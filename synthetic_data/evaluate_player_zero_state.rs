@@ -1,8 +1,6 @@
-use splendor::game_state::GameState;
+use splendor::game_state::{GameState, UniquePositions};
 
-use crate::constants::WINNING_POINTS;
 use crate::generate_traces_from_player_zero_state::generate_traces_from_player_zero_state;
-use crate::getters::{get_last_player_points, get_value};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EvaluationResult {
@@ -22,64 +20,54 @@ impl EvaluationResult {
     }
 }
 
+/// Result of a finished game from player 0's point of view, None while the game goes on
+fn get_player_zero_result(state: &GameState) -> Option<EvaluationResult> {
+    let result = state.get_result()?;
+    if !result.is_winner(0) {
+        Some(EvaluationResult::Losing)
+    } else if result.is_shared() {
+        Some(EvaluationResult::Draw)
+    } else {
+        Some(EvaluationResult::Winning)
+    }
+}
+
 /// Evaluate if a player 0 state is winning, losing, or draw
-pub fn evaluate_player_zero_state(player_zero_state: &GameState, n_players: u8, max_depth: u8) -> EvaluationResult {
+pub fn evaluate_player_zero_state(player_zero_state: &GameState, max_depth: u8) -> EvaluationResult {
     if max_depth == 0 {
         return EvaluationResult::Draw;
     }
-    let traces = generate_traces_from_player_zero_state(player_zero_state, n_players);
+    let traces = generate_traces_from_player_zero_state(player_zero_state);
 
     let mut all_children_losing = true;
     let mut has_draw_child = false;
-    let mut player_zero_states_to_recurse: Vec<GameState> = Vec::new();
-
-    // Evaluate each child state and its traces
-    for (child_state, trace_list) in traces {
-        let child_value = get_value(&child_state, n_players);
-
-        // Check if child has winning points and is better than all states in its traces
-        if child_value.0 >= WINNING_POINTS {
-            let is_better_than_all = trace_list.iter().all(|trace| {
-                trace.iter().all(|state| {
-                    let state_value = get_value(state, n_players);
-                    child_value > state_value
-                })
-            });
-
-            if is_better_than_all {
-                return EvaluationResult::Winning;
-            }
+    let mut player_zero_states_to_recurse = UniquePositions::new();
 
-            // Check if child has equal value to any state in traces (only when game ends)
-            let has_equal_state = trace_list.iter().any(|trace| {
-                trace.iter().any(|state| {
-                    let state_value = get_value(state, n_players);
-                    child_value == state_value
-                })
-            });
+    // Evaluate each child state and its traces, a trace ends when the round is complete
+    for (_, round_ends) in traces {
+        let results: Vec<Option<EvaluationResult>> = round_ends
+            .iter()
+            .map(get_player_zero_result)
+            .collect();
 
-            if has_equal_state {
-                has_draw_child = true;
-            }
+        // Winning if the game ends with player 0 ahead whatever the others play
+        if !results.is_empty() && results.iter().all(|result| *result == Some(EvaluationResult::Winning)) {
+            return EvaluationResult::Winning;
         }
 
-        // Check if child is losing (has a state in its traces that's better)
-        let has_better_state = trace_list.iter().any(|trace| {
-            trace.iter().any(|state| {
-                get_last_player_points(state, n_players) >= WINNING_POINTS
-            })
-        });
-
-        if !has_better_state {
-            all_children_losing = false;
+        // Child is losing if any reply ends the game without player 0 winning
+        if results.contains(&Some(EvaluationResult::Losing)) {
+            continue;
+        }
+        all_children_losing = false;
+        if results.contains(&Some(EvaluationResult::Draw)) {
+            has_draw_child = true;
+        }
 
-            // Collect last states from traces for recursion
-            for trace in trace_list {
-                if let Some(last_state) = trace.last() {
-                    if last_state.get_current_player_index() == 0 {
-                        player_zero_states_to_recurse.push(last_state.clone());
-                    }
-                }
+        // Collect unfinished round ends for recursion
+        for (state, result) in round_ends.into_iter().zip(results) {
+            if result.is_none() && state.get_current_player_index() == 0 {
+                player_zero_states_to_recurse.insert(state);
             }
         }
     }
@@ -90,7 +78,7 @@ pub fn evaluate_player_zero_state(player_zero_state: &GameState, n_players: u8,
 
     // Recurse on collected player 0 states
     for state in player_zero_states_to_recurse {
-        let evaluation_result = evaluate_player_zero_state(&state, n_players, max_depth - 1);
+        let evaluation_result = evaluate_player_zero_state(&state, max_depth - 1);
         if evaluation_result == EvaluationResult::Winning {
             return EvaluationResult::Winning;
         }
@@ -1,58 +1,59 @@
 use rand::Rng;
-use splendor::game_state::{create_initial_game_state, GameState};
-use splendor::moves::all_moves::get_all_moves;
+use splendor::game_record::GameRecord;
+use splendor::game_state::{create_initial_game_state, GameState, TurnPhase};
+use splendor::rule_set::RuleSet;
 use splendor::state_encoder::StateEncoder;
 use std::collections::VecDeque;
 
-use super::constants::WINNING_POINTS;
 use super::evaluate_player_zero_state::evaluate_player_zero_state;
-use super::getters::get_last_player_points;
 use super::state_to_bytes::game_state_to_bytes;
 
-fn play_game<R: Rng>(n_players: u8, rng: &mut R, state_history: &mut VecDeque<GameState>) -> (i32, GameState) {
-    let mut current_state = create_initial_game_state(n_players, rng);
+/// Name of the random policy in the game records
+const AGENT_NAME: &str = "random";
+
+fn play_game<R: Rng>(rules: RuleSet, rng: &mut R, state_history: &mut VecDeque<GameState>) -> (i32, GameRecord) {
+    let names = vec![AGENT_NAME.to_string(); rules.n_players() as usize];
+    let mut record = GameRecord::new(create_initial_game_state(rules, rng))
+        .with_names(names)
+        .expect("One name is given per player");
     let mut move_num = 0;
     loop {
-        move_num += 1;
-        let all_moves = get_all_moves();
-        let valid_move_indices: Vec<usize> = all_moves
-            .iter()
-            .enumerate()
-            .filter(|(_, m)| m.is_valid(&current_state))
-            .map(|(i, _)| i)
-            .collect();
-        if valid_move_indices.is_empty() {
-            state_history.clear();
-            return play_game(n_players, rng, state_history);
+        let current_state = record.get_final_state();
+        if current_state.starts_turn() {
+            move_num += 1;
         }
-        let random_index = valid_move_indices[rng.gen_range(0..valid_move_indices.len())];
-        let chosen_move = &all_moves[random_index];
+        let legal_actions = current_state.legal_actions();
+        let chosen_move = legal_actions[rng.gen_range(0..legal_actions.len())];
         state_history.push_back(current_state.clone());
-        current_state = chosen_move.perform(&current_state);
-        if get_last_player_points(&current_state, n_players) >= WINNING_POINTS {
-            return (move_num, current_state);
+        record.push(chosen_move, None).expect("Valid moves can always be played");
+        if record.get_final_state().is_game_over() {
+            return (move_num, record);
         }
     }
 }
 
+/// Encoded states, labels, numbers of moves, RNG snapshots and records of the games kept
+pub type GeneratedData<R> = (Vec<Vec<u8>>, Vec<i8>, Vec<u8>, Vec<R>, Vec<GameRecord>);
+
 pub fn generate_synthetic_data<R: Rng + Clone>(
     num_games: u32,
-    n_players: u8,
+    rules: RuleSet,
     rng: &mut R,
     n_moves_limit: i32,
     max_depth: u8,
     encoder: &dyn StateEncoder,
-) -> (Vec<Vec<u8>>, Vec<i8>, Vec<u8>, Vec<R>) {
-    let history_size = n_players as usize;
+) -> GeneratedData<R> {
+    let history_size = rules.n_players() as usize;
     let mut all_states: Vec<Vec<u8>> = Vec::new();
     let mut all_labels: Vec<i8> = Vec::new();
     let mut all_n_moves: Vec<u8> = Vec::new();
     let mut rng_states_before_requirement: Vec<R> = Vec::new();
+    let mut records: Vec<GameRecord> = Vec::new();
     let mut games_generated = 0;
     while games_generated < num_games {
         let rng_snapshot = rng.clone();
         let mut state_history: VecDeque<GameState> = VecDeque::with_capacity(history_size);
-        let (n_moves, _final_state) = play_game(n_players, rng, &mut state_history);
+        let (n_moves, record) = play_game(rules, rng, &mut state_history);
         if n_moves > n_moves_limit {
             continue;
         }
@@ -60,14 +61,15 @@ pub fn generate_synthetic_data<R: Rng + Clone>(
         let player_zero_state = state_history
             .iter()
             .rev()
-            .find(|state| state.get_current_player_index() == 0)
+            .find(|state| state.get_current_player_index() == 0 && state.get_phase() == TurnPhase::Action)
             .expect("Player zero state must exist in history");
-        let evaluation_result = evaluate_player_zero_state(player_zero_state, n_players, max_depth);
+        let evaluation_result = evaluate_player_zero_state(player_zero_state, max_depth);
         let state_bytes = game_state_to_bytes(player_zero_state, encoder);
         all_states.push(state_bytes);
         all_labels.push(evaluation_result.to_label());
         all_n_moves.push(n_moves as u8);
+        records.push(record);
         games_generated += 1;
     }
-    (all_states, all_labels, all_n_moves, rng_states_before_requirement)
+    (all_states, all_labels, all_n_moves, rng_states_before_requirement, records)
 }
@@ -1,9 +1,10 @@
+use splendor::deck::Deck;
+use splendor::game_record::GameRecord;
+use splendor::rule_set::RuleSet;
 use splendor::state_encoder::{OneHotCardEncoder, ParameterEncoder, StateEncoder};
 use std::env;
 use std::fs;
 
-mod constants;
-mod getters;
 mod generate_traces_from_player_zero_state;
 mod evaluate_player_zero_state;
 mod save_data;
@@ -18,9 +19,9 @@ use generator::generate_synthetic_data;
 use save_data::save_states_with_labels;
 use rng_state::{create_or_load_rng, save_rng_states_batch};
 
-fn create_encoder(use_one_hot: bool) -> Box<dyn StateEncoder> {
+fn create_encoder(use_one_hot: bool, deck: &Deck) -> Box<dyn StateEncoder> {
     if use_one_hot {
-        Box::new(OneHotCardEncoder::new())
+        Box::new(OneHotCardEncoder::new(deck))
     } else {
         Box::new(ParameterEncoder::new())
     }
@@ -28,7 +29,7 @@ fn create_encoder(use_one_hot: bool) -> Box<dyn StateEncoder> {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let n_players: u8 = 2;
+    let rules = RuleSet::official(2).expect("2 players are supported");
     let num_games: u32 = if args.len() > 1 {
         args[1].parse().expect("First argument must be a valid number of games")
     } else {
@@ -63,7 +64,7 @@ fn main() {
     let save_interval: u32 = 1000;
     fs::create_dir_all(&output_dir).expect("Failed to create output directory");
     fs::create_dir_all(&rng_states_dir).expect("Failed to create rng_states directory");
-    let encoder = create_encoder(use_one_hot_encoder);
+    let encoder = create_encoder(use_one_hot_encoder, rules.deck());
     let initial_rng_path = format!("{}/rng_state_0.bin", rng_states_dir);
     let mut rng = create_or_load_rng(seed, &initial_rng_path);
     println!("Running {} games (one-hot: {})...", num_games, use_one_hot_encoder);
@@ -73,10 +74,11 @@ fn main() {
     let mut all_labels: Vec<i8> = Vec::new();
     let mut all_n_moves: Vec<u8> = Vec::new();
     let mut all_rng_states = Vec::new();
+    let mut all_records: Vec<GameRecord> = Vec::new();
     for game_num in 1..=num_games {
-        let (states, labels, n_moves, rng_states) = generate_synthetic_data(
+        let (states, labels, n_moves, rng_states, records) = generate_synthetic_data(
             1,
-            n_players,
+            rules,
             &mut rng,
             n_moves_limit,
             max_depth,
@@ -86,6 +88,7 @@ fn main() {
         all_labels.extend(labels);
         all_n_moves.extend(n_moves);
         all_rng_states.extend(rng_states);
+        all_records.extend(records);
         if game_num % save_interval == 0 {
             println!("Saving checkpoint at {} / {} games...", game_num, num_games);
             save_states_with_labels(
@@ -100,10 +103,13 @@ fn main() {
             let rng_states_path = format!("{}/rng_states_{}.bin", rng_states_dir, game_num);
             save_rng_states_batch(&all_rng_states, &rng_states_path)
                 .expect("Failed to save RNG states batch");
+            fs::write(format!("{}/games_{}.txt", output_dir, game_num), GameRecord::write_all(&all_records))
+                .expect("Failed to save game records");
             all_states.clear();
             all_labels.clear();
             all_n_moves.clear();
             all_rng_states.clear();
+            all_records.clear();
         }
         if game_num % 100 == 0 {
             println!("Completed {} / {} games", game_num, num_games);
@@ -123,5 +129,7 @@ fn main() {
         let rng_states_path = format!("{}/rng_states_{}.bin", rng_states_dir, num_games);
         save_rng_states_batch(&all_rng_states, &rng_states_path)
             .expect("Failed to save final RNG states batch");
+        fs::write(format!("{}/games_{}.txt", output_dir, num_games), GameRecord::write_all(&all_records))
+            .expect("Failed to save final game records");
     }
 }
\ No newline at end of file
@@ -1,51 +1,13 @@
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use splendor::game_record::GameRecord;
 use splendor::game_state::{create_initial_game_state, GameState};
-use splendor::moves::all_moves::get_all_moves;
+use splendor::rule_set::RuleSet;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
 use std::path::Path;
 
-fn decode_move_index(index: usize) -> String {
-    match index {
-        0 => "Get 3: Green, Blue, Red".to_string(),
-        1 => "Get 3: Green, Blue, White".to_string(),
-        2 => "Get 3: Green, Blue, Black".to_string(),
-        3 => "Get 3: Green, Red, White".to_string(),
-        4 => "Get 3: Green, Red, Black".to_string(),
-        5 => "Get 3: Green, White, Black".to_string(),
-        6 => "Get 3: Blue, Red, White".to_string(),
-        7 => "Get 3: Blue, Red, Black".to_string(),
-        8 => "Get 3: Blue, White, Black".to_string(),
-        9 => "Get 3: Red, White, Black".to_string(),
-        10 => "Get 2: Green".to_string(),
-        11 => "Get 2: Blue".to_string(),
-        12 => "Get 2: Red".to_string(),
-        13 => "Get 2: White".to_string(),
-        14 => "Get 2: Black".to_string(),
-        15..=38 => {
-            let offset = index - 15;
-            let tier_index = offset / 8;
-            let within_tier = offset % 8;
-            let card_index = within_tier / 2;
-            let is_build = within_tier % 2 == 1;
-            if is_build {
-                format!("Build card from board (Tier {}, Position {})", tier_index + 1, card_index)
-            } else {
-                format!("Reserve card from board (Tier {}, Position {})", tier_index + 1, card_index)
-            }
-        }
-        39 => "Reserve from hidden deck (Tier 1)".to_string(),
-        40 => "Reserve from hidden deck (Tier 2)".to_string(),
-        41 => "Reserve from hidden deck (Tier 3)".to_string(),
-        42 => "Build from reserve (Slot 0)".to_string(),
-        43 => "Build from reserve (Slot 1)".to_string(),
-        44 => "Build from reserve (Slot 2)".to_string(),
-        _ => format!("Unknown move index: {}", index),
-    }
-}
-
 fn load_rng_states_batch(path: &str) -> Result<Vec<ChaCha8Rng>, Box<dyn std::error::Error>> {
     let mut file = File::open(path)?;
     let mut buffer = Vec::new();
@@ -79,95 +41,98 @@ fn format_player_state(state: &GameState, player_index: usize) -> String {
     )
 }
 
-fn play_and_print_moves<R: Rng>(n_players: u8, rng: &mut R, winner_only: bool) -> GameState {
-    let mut current_state = create_initial_game_state(n_players, rng);
-    let mut move_num = 0;
-    let all_moves = get_all_moves();
+fn play_random_game<R: Rng>(rules: RuleSet, rng: &mut R) -> GameRecord {
+    let mut record = GameRecord::new(create_initial_game_state(rules, rng));
+    while !record.get_final_state().is_game_over() {
+        let legal_actions = record.get_final_state().legal_actions();
+        let chosen_move = legal_actions[rng.gen_range(0..legal_actions.len())];
+        record.push(chosen_move, None).expect("Valid moves can always be played");
+    }
+    record
+}
+
+fn print_record(record: &GameRecord, winner_only: bool) {
+    let states = record.states();
     let mut move_history: Vec<(usize, usize, String, String)> = Vec::new();
     if !winner_only {
         println!("\n=== Starting Game ===");
-        println!("Initial state: {} players", n_players);
+        println!("Initial state: {} players", record.get_initial_state().get_rules().n_players());
     }
-    loop {
-        move_num += 1;
-        let valid_move_indices: Vec<usize> = all_moves
-            .iter()
-            .enumerate()
-            .filter(|(_, m)| m.is_valid(&current_state))
-            .map(|(i, _)| i)
-            .collect();
-        if valid_move_indices.is_empty() {
-            if !winner_only {
-                println!("\nNo valid moves available! Game restarted.");
-            }
-            move_history.clear();
-            return play_and_print_moves(n_players, rng, winner_only);
+    for (index, &(chosen_move, _)) in record.get_moves().iter().enumerate() {
+        let move_num = index + 1;
+        let current_player = states[index].get_current_player_index();
+        let move_description = chosen_move.to_string();
+        let player_state = format_player_state(&states[index + 1], current_player);
+        if winner_only {
+            move_history.push((move_num, current_player, move_description, player_state));
+        } else {
+            println!("Move {}: Player {} - {}", move_num, current_player, move_description);
+            println!("  -> Player {} state: {}", current_player, player_state);
         }
-        let random_index = valid_move_indices[rng.gen_range(0..valid_move_indices.len())];
-        let chosen_move = &all_moves[random_index];
-        let current_player = current_state.get_current_player_index();
-        let move_description = decode_move_index(random_index);
-        current_state = chosen_move.perform(&current_state);
-        let acting_player = if current_state.get_current_player_index() == 0 {
-            n_players - 1
+    }
+    let move_num = record.get_moves().len();
+    let final_state = record.get_final_state();
+    let Some(result) = record.get_result() else {
+        println!("\n=== Game not finished after {} moves ===", move_num);
+        return;
+    };
+    let winners = result.get_winners();
+    if winner_only {
+        if result.is_shared() {
+            println!("\n=== Winners: Players {:?} ===", winners);
         } else {
-            (current_state.get_current_player_index() - 1) as u8
-        };
-        let player_state = format_player_state(&current_state, acting_player as usize);
-        if winner_only {
-            move_history.push((move_num, current_player as usize, move_description.clone(), player_state));
+            println!("\n=== Winner: Player {} ===", winners[0]);
+        }
+        println!("Total moves: {}", move_num);
+        println!("\nWinner's moves:");
+        for (move_n, player, description, state) in move_history.iter() {
+            if winners.contains(player) {
+                println!("  Move {}: Player {} - {}", move_n, player, description);
+                println!("    -> {}", state);
+            }
+        }
+    } else {
+        if result.is_shared() {
+            println!("\n=== Game Over ===\nPlayers {:?} share the win after {} moves!", winners, move_num);
         } else {
             println!(
-                "Move {}: Player {} - {}",
-                move_num, current_player, move_description
+                "\n=== Game Over ===\nPlayer {} wins with {} points after {} moves!",
+                winners[0], final_state.get_players()[winners[0]].get_points(), move_num
             );
-            println!("  -> Player {} state: {}", acting_player, player_state);
         }
-        let last_player = if current_state.get_current_player_index() == 0 {
-            n_players - 1
-        } else {
-            (current_state.get_current_player_index() - 1) as u8
-        };
-        let last_player_points = current_state.get_players()[last_player as usize].get_points();
-        if last_player_points >= 15 {
-            if winner_only {
-                println!("\n=== Winner: Player {} ===", last_player);
-                println!("Total moves: {}", move_num);
-                println!("\nWinner's moves:");
-                for (move_n, player, description, state) in move_history.iter() {
-                    if *player == last_player as usize {
-                        println!("  Move {}: {}", move_n, description);
-                        println!("    -> {}", state);
-                    }
-                }
-            } else {
-                println!(
-                    "\n=== Game Over ===\nPlayer {} wins with {} points after {} moves!",
-                    last_player, last_player_points, move_num
-                );
-                println!("\nFinal state of Player {}:", last_player);
-                println!("  {}", format_player_state(&current_state, last_player as usize));
-            }
-            return current_state;
+        for &winner in winners {
+            println!("\nFinal state of Player {}:", winner);
+            println!("  {}", format_player_state(final_state, winner));
         }
     }
 }
 
+fn load_records(path: &str) -> Result<Vec<GameRecord>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)?;
+    Ok(GameRecord::read_all(&text)?)
+}
+
+fn print_usage(args: &[String]) {
+    eprintln!("Usage:");
+    eprintln!("  {} <state_index> [base_seed] [--winner-only]", args[0]);
+    eprintln!("  {} <rng_states_file> <state_index> [--winner-only]", args[0]);
+    eprintln!("  {} <games_file> <game_index> [--winner-only]", args[0]);
+    eprintln!("\nExamples:");
+    eprintln!("  {} 454 42                                      # Use seed-based RNG (seed 42+454)", args[0]);
+    eprintln!("  {} rng_states/rng_states_160000.bin 454        # Load from batch file", args[0]);
+    eprintln!("  {} rng_states/rng_states_160000.bin 454 --winner-only  # Show only winner's moves", args[0]);
+    eprintln!("  {} games_160000.txt 454                         # Replay a recorded game", args[0]);
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        eprintln!("Usage:");
-        eprintln!("  {} <state_index> [base_seed] [--winner-only]", args[0]);
-        eprintln!("  {} <rng_states_file> <state_index> [--winner-only]", args[0]);
-        eprintln!("\nExamples:");
-        eprintln!("  {} 454 42                                      # Use seed-based RNG (seed 42+454)", args[0]);
-        eprintln!("  {} rng_states/rng_states_160000.bin 454        # Load from batch file", args[0]);
-        eprintln!("  {} rng_states/rng_states_160000.bin 454 --winner-only  # Show only winner's moves", args[0]);
+        print_usage(&args);
         std::process::exit(1);
     }
     let winner_only = args.iter().any(|arg| arg == "--winner-only");
-    let n_players = 2;
-    let mut rng = if args.len() >= 2 && args[1].parse::<usize>().is_ok() {
+    let rules = RuleSet::official(2).expect("2 players are supported");
+    let record = if args.len() >= 2 && args[1].parse::<usize>().is_ok() {
         let state_index: usize = args[1].parse().expect("state_index must be a valid number");
         let base_seed: u64 = if args.len() >= 3 && args[2] != "--winner-only" {
             args[2].parse().expect("base_seed must be a valid number")
@@ -178,7 +143,29 @@ fn main() {
             println!("Using seed-based RNG: base_seed={}, index={}", base_seed, state_index);
             println!("Effective seed: {}", base_seed.wrapping_add(state_index as u64));
         }
-        get_rng_from_index(base_seed, state_index)
+        play_random_game(rules, &mut get_rng_from_index(base_seed, state_index))
+    } else if args.len() < 3 {
+        // Files are followed by the index of the game or state to replay
+        print_usage(&args);
+        std::process::exit(1);
+    } else if !args[1].ends_with(".bin") {
+        let games_file = &args[1];
+        let game_index: usize = args[2].parse().expect("game_index must be a valid number");
+        if !winner_only {
+            println!("Loading game records from: {}", games_file);
+        }
+        let mut records = match load_records(games_file) {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("Failed to load game records: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if game_index >= records.len() {
+            eprintln!("Error: game_index {} is out of bounds ({} games recorded)", game_index, records.len());
+            std::process::exit(1);
+        }
+        records.swap_remove(game_index)
     } else {
         let rng_states_file = &args[1];
         let state_index: usize = args[2].parse().expect("state_index must be a valid number");
@@ -212,7 +199,7 @@ fn main() {
         if !winner_only {
             println!("Using RNG state at index {}", state_index);
         }
-        rng_states[state_index].clone()
+        play_random_game(rules, &mut rng_states[state_index].clone())
     };
-    play_and_print_moves(n_players, &mut rng, winner_only);
+    print_record(&record, winner_only);
 }
\ No newline at end of file
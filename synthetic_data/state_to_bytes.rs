@@ -1,9 +1,10 @@
 use splendor::game_state::GameState;
 use splendor::card::card::Card;
 use splendor::resource::Resource;
-use splendor::state_encoder::StateEncoder;
+use splendor::state_encoder::{encode_turn, StateEncoder};
 
-fn add_card_to_state(state: &mut Vec<u8>, card: Option<&&Card>) {
+/// Cards reserved face down by an opponent are unknown, only the tier written before them tells them from an empty slot
+fn add_card_to_state(state: &mut Vec<u8>, card: Option<&Card>) {
     if let Some(card) = card {
         state.push(card.n_points());
         state.push(card.cost().n_green());
@@ -29,18 +30,23 @@ fn add_card_to_state(state: &mut Vec<u8>, card: Option<&&Card>) {
     }
 }
 
+/// Features of the state as the current player sees it, laid out as `SplendorGame.get_game_state`:
+/// 48 bytes per player, the turn phase and pending payment, then the rows
 pub fn game_state_to_bytes(game_state: &GameState, encoder: &dyn StateEncoder) -> Vec<u8> {
     let mut output = Vec::new();
+    let observation = game_state
+        .observe(game_state.get_current_player_index())
+        .expect("The current player index always points to a player");
 
     // Add players in turn order (current player first)
-    let players_in_order: Vec<_> = game_state
+    let players_in_order: Vec<_> = observation
         .get_players()
         .iter()
-        .chain(game_state.get_players().iter())
+        .chain(observation.get_players().iter())
         .collect();
 
-    let current_idx = game_state.get_current_player_index();
-    let n_players = game_state.get_players().len();
+    let current_idx = observation.get_current_player_index();
+    let n_players = observation.get_players().len();
 
     for player in &players_in_order[current_idx..current_idx + n_players] {
         output.push(player.get_points());
@@ -60,13 +66,15 @@ pub fn game_state_to_bytes(game_state: &GameState, encoder: &dyn StateEncoder) -
         output.push(production.n_black());
         output.push(production.n_white());
 
-        // Add reserved cards (up to 3)
+        // Add reserved cards (up to 3), each after its tier or 0 for an empty slot
         for i in 0..3 {
-            add_card_to_state(&mut output, player.get_reserve().get(i));
+            let reserved = player.get_reserve().get(i);
+            output.push(reserved.map_or(0, |card| card.get_tier().number()));
+            add_card_to_state(&mut output, reserved.and_then(|card| card.get_card()));
         }
     }
 
-    let rows = game_state.get_board().get_rows();
-    output.extend(encoder.encode_rows(rows));
+    output.extend(encode_turn(&observation));
+    output.extend(encoder.encode_rows(&observation));
     output
 }